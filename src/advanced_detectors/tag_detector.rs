@@ -52,10 +52,7 @@ impl TagDetector {
         allowed_attributes: Option<Vec<(String, Option<WordDetector>)>>
     ) -> Self {
         Self {
-            tag: match tag {
-                Some(tag) => Some(Regex::new(tag.as_str()).ok().unwrap()),
-                None => None
-            },
+            tag: tag.map(|tag| Regex::new(tag.as_str()).ok().unwrap()),
             has_attributes,
             is_closing,
             is_self_closing,
@@ -108,16 +105,21 @@ impl Detectable for TagDetector {
             Some(inner_result) => {
                 let mut queue = inner_result.content.unwrap();
 
+                // An empty tag (`<>`) has no name
+                if queue.is_empty() {
+                    return None;
+                }
+
                 let closing;
 
                 // Check if the tag is closing
                 if queue[0] == '/' {
                     closing = true;
 
-                    if self.is_closing.unwrap_or(true) == false {
+                    if !self.is_closing.unwrap_or(true) {
                         return None;
                     }
-                    if self.is_opening.unwrap_or(false) == true {
+                    if self.is_opening.unwrap_or(false) {
                         return None;
                     }
 
@@ -125,10 +127,10 @@ impl Detectable for TagDetector {
                 } else {
                     closing = false;
 
-                    if self.is_opening.unwrap_or(true) == false {
+                    if !self.is_opening.unwrap_or(true) {
                         return None;
                     }
-                    if self.is_closing.unwrap_or(false) == true {
+                    if self.is_closing.unwrap_or(false) {
                         return None;
                     }
                 }
@@ -149,13 +151,10 @@ impl Detectable for TagDetector {
                 let tag = tag.unwrap();
 
                 // Check if the tag is the correct tag
-                match &self.tag {
-                    Some(tag_name) => {
-                        if !tag_name.is_match(tag.as_str()) {
-                            return None;
-                        }
-                    },
-                    None => {}
+                if let Some(tag_name) = &self.tag {
+                    if !tag_name.is_match(tag.as_str()) {
+                        return None;
+                    }
                 }
 
                 // While there are attributes, consume them
@@ -170,38 +169,32 @@ impl Detectable for TagDetector {
                         break;
                     }
 
-                    match result {
-                        Some(result) => {
-                            match result.properties {
-                                Some(properties) => {
-                                    // Get key and value
-                                    let key = properties.get("key");
-                                    let value = properties.get("value");
-
-                                    // Check if the attribute is already defined
-                                    if attributes_pre.has(key.to_str().unwrap().as_str()) {
-                                        return None;
-                                    }
+                    if let Some(result) = result {
+                        if let Some(properties) = result.properties {
+                            // Get key and value
+                            let key = properties.get("key");
+                            let value = properties.get("value");
 
-                                    // Add the attribute to the list
-                                    attributes_pre.set(
-                                        key.to_str().unwrap().as_str(),
-                                        value
-                                    );
-                                },
-                                None => {}
+                            // Check if the attribute is already defined
+                            if attributes_pre.has(key.to_str().unwrap().as_str()) {
+                                return None;
                             }
-                        },
-                        None => {}
+
+                            // Add the attribute to the list
+                            attributes_pre.set(
+                                key.to_str().unwrap().as_str(),
+                                value
+                            );
+                        }
                     }
                 }
 
                 if !attributes_pre.empty() {
-                    if self.has_attributes.unwrap_or(true) == false || closing {
+                    if !self.has_attributes.unwrap_or(true) || closing {
                         return None;
                     }
                 } else {
-                    if self.has_attributes.unwrap_or(false) == true {
+                    if self.has_attributes.unwrap_or(false) {
                         return None;
                     }
                 }
@@ -216,14 +209,11 @@ impl Detectable for TagDetector {
                                 if *allowed_key == key {
                                     match allowed_value {
                                         Some(allowed_value) => {
-                                            match allowed_value.detect(&mut Queue::from_string(value.clone().to_str().unwrap_or("".to_string()))) {
-                                                Some(_) => {
-                                                    attributes.set(
-                                                        key.as_str(),
-                                                        value.clone()
-                                                    );
-                                                },
-                                                None => {}
+                                            if allowed_value.detect(&mut Queue::from_string(value.clone().to_str().unwrap_or("".to_string()))).is_some() {
+                                                attributes.set(
+                                                    key.as_str(),
+                                                    value.clone()
+                                                );
                                             }
                                         },
                                         None => {
@@ -251,16 +241,16 @@ impl Detectable for TagDetector {
                 let self_closing: bool;
 
                 // Check if the tag is self-closing
-                if queue.len() == 0 || queue[0] != '/' {
+                if queue.is_empty() || queue[0] != '/' {
                     self_closing = false;
 
-                    if self.is_self_closing.unwrap_or(false) == true {
+                    if self.is_self_closing.unwrap_or(false) {
                         return None;
                     }
                 } else {
                     self_closing = true;
 
-                    if self.is_self_closing.unwrap_or(true) == false {
+                    if !self.is_self_closing.unwrap_or(true) {
                         return None;
                     }
 
@@ -482,6 +472,11 @@ mod tests {
 
         test_open_and_close(&mut queue_opening_attributes_span.clone());
         test_self_closing_and_close(&mut queue_opening_attributes_span.clone());
+
+        let queue_empty = Queue::from_string("<>".to_string());
+
+        test_any(&mut queue_empty.clone(), false);
+        test_is_closing(&mut queue_empty.clone(), Some(true), false);
     }
 
     fn passed(name: &str, params: &str, queue: &str) {
@@ -520,10 +515,7 @@ mod tests {
     }
 
     fn test_tag_name(queue: &mut Queue, tag_name: Option<&str>, wanted: bool) {
-        let tag = match tag_name {
-            Some(tag_name) => Some(tag_name.to_string()),
-            None => None
-        };
+        let tag = tag_name.map(|tag_name| tag_name.to_string());
 
         let detector = Detector::TagDetector(
             TagDetector::new(
@@ -23,10 +23,7 @@ pub struct TagScopeDetector {
 impl TagScopeDetector {
     pub fn new(tag: Option<String>, id: Option<String>, class: Option<Vec<String>>, allow_inner: Option<bool>, is_standalone: Option<bool>, allow_self_closing: Option<bool>, allowed_attributes: Option<Vec<(String, Option<WordDetector>)>>) -> Self {
         Self {
-            tag: match tag {
-                Some(tag) => Some(Regex::new(&format!(r"^{}$", tag)).unwrap()),
-                None => None
-            },
+            tag: tag.map(|tag| Regex::new(&format!(r"^{}$", tag)).unwrap()),
             id,
             class,
             allow_inner,
@@ -103,14 +100,12 @@ impl Detectable for TagScopeDetector {
 
         let classes_string = class.clone();
 
-        let classes: Vec<Value> = match classes_string.split(" ").map(|class| Value::String(class.to_string())).collect::<Vec<Value>>() {
-            classes => {
-                if classes.len() == 1 && classes[0] == Value::String("".to_string()) {
-                    vec![]
-                } else {
-                    classes
-                }
-            }
+        let classes: Vec<Value> = classes_string.split(' ').map(|class| Value::String(class.to_string())).collect::<Vec<Value>>();
+
+        let classes = if classes.len() == 1 && classes[0] == Value::String("".to_string()) {
+            vec![]
+        } else {
+            classes
         };
 
         attributes.set("class", Value::Array(classes.clone()));
@@ -202,14 +197,11 @@ impl Detectable for TagScopeDetector {
 
         // Set inner as inner scope result content
         let inner = match inner_scope_result {
-            Some(result) => match result.content {
-                Some(inner) => inner,
-                None => return None
-            },
+            Some(result) => result.content?,
             None => return None
         };
 
-        if !self.allow_inner.unwrap_or(true) && inner.len() > 0 {
+        if !self.allow_inner.unwrap_or(true) && !inner.is_empty() {
             return None;
         }
 
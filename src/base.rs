@@ -6,6 +6,9 @@
 use std::{fmt::Debug, vec};
 
 use crate::html::HTMLDetector;
+use crate::markdown::MarkdownDetector;
+use crate::markdown::paragraph::paragraphs;
+use crate::markdown::line::dedent;
 
 use crate::{
     detectors::{
@@ -40,6 +43,77 @@ impl Result {
         }
     }
 
+    /// Block phase: parses block content into the container tree (inline leaves are left as content or raw text)
+    pub fn parse_blocks(&mut self, detectors: &[Detector]) {
+        // Raw results are never parsed again
+        if self.detector == Detector::RawDetector {
+            return;
+        }
+
+        match (&self.content, &mut self.children) {
+            (Some(_), _) if self.detector.has_block_content() => {
+                self.children = self.block_content().consume_blocks(detectors);
+            },
+            (None, Some(children)) => {
+                for child in children.iter_mut() {
+                    child.parse_blocks(detectors);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    /// Content of a container as parsed in the block phase (indented HTML is not code)
+    fn block_content(&self) -> Queue {
+        let content = self.content.clone().unwrap_or_default();
+
+        match self.detector {
+            Detector::HTMLDetector(_) => dedent(&content),
+            _ => content
+        }
+    }
+
+    /// Inline phase: parses the content of the leaves and the raw text of the containers
+    pub fn parse_inlines(&mut self, detectors: &[Detector]) {
+        if self.detector == Detector::RawDetector {
+            return;
+        }
+
+        if self.detector.has_block_content() {
+            self.children = match self.children.take() {
+                Some(children) => Some(parse_inlines(children, detectors)),
+                // Containers detected in the inline phase have not been through the block phase yet
+                None => match &self.content {
+                    Some(content) if !content.is_empty() => self.block_content().consume_any(detectors),
+                    _ => None
+                }
+            };
+
+            return;
+        }
+
+        match (&self.content, &mut self.children) {
+            (Some(content), _) => {
+                if !content.is_empty() {
+                    self.children = content.clone().consume_inlines(detectors);
+                }
+            },
+            (None, Some(children)) => {
+                for child in children.iter_mut() {
+                    child.parse_inlines(detectors);
+                }
+            },
+            (None, None) => {}
+        }
+    }
+
+    pub fn set_property(&mut self, key: &str, value: Value) {
+        match &mut self.properties {
+            Some(properties) => properties.set(key, value),
+            None => self.properties = Some(Dict::from_values(vec![(key.to_string(), value)]))
+        }
+    }
+
     pub fn get_property(&self, key: &str) -> Value {
         match &self.properties {
             Some(properties) => properties.get(key),
@@ -57,6 +131,34 @@ impl PartialEq for Result {
     }
 }
 
+/// Inline phase for the children of a container, raw text between the blocks is replaced by its inline results
+pub fn parse_inlines(results: Vec<Result>, detectors: &[Detector]) -> Vec<Result> {
+    results.into_iter().flat_map(|mut result| {
+        if result.detector == Detector::RawDetector {
+            return match &result.content {
+                Some(content) if !content.is_empty() => content.clone().consume_inlines(detectors).unwrap_or_default(),
+                _ => vec![]
+            };
+        }
+
+        result.parse_inlines(detectors);
+
+        vec![result]
+    }).collect()
+}
+
+/// Length of the text the detectors without block support see first in the block phase
+pub const BLOCK_WINDOW: usize = 4096;
+
+/// Text between blocks, either as raw text or grouped into paragraphs
+fn text_results(text: Queue, line: i64, grouped: bool) -> Vec<Result> {
+    if grouped {
+        paragraphs(&text, line)
+    } else {
+        vec![Result::new(Detector::RawDetector, Some(text), None, None)]
+    }
+}
+
 /// Detectable Trait (A object that can detect a pattern from a queue)
 pub trait Detectable: Debug + Clone + PartialEq {
     fn detect(&self, queue: &mut Queue) -> Option<Result>;
@@ -64,7 +166,9 @@ pub trait Detectable: Debug + Clone + PartialEq {
 
 pub trait Consumable {
     fn consume(&mut self, detector: &Detector) -> (bool, Option<String>, Option<Result>);
-    fn consume_any(&mut self, detectors: &Vec<Detector>) -> Option<Vec<Result>> ;
+    fn consume_any(&mut self, detectors: &[Detector]) -> Option<Vec<Result>>;
+    fn consume_blocks(&mut self, detectors: &[Detector]) -> Option<Vec<Result>>;
+    fn consume_inlines(&mut self, detectors: &[Detector]) -> Option<Vec<Result>>;
     fn from_string(string: String) -> Self;
     fn to_string(&self) -> String;
 }
@@ -79,10 +183,80 @@ pub enum Detector {
     TagDetector(TagDetector),
     TagScopeDetector(TagScopeDetector),
     HTMLDetector(HTMLDetector),
+    MarkdownDetector(MarkdownDetector),
     RawDetector,
     NoneDetector
 }
 
+impl Detector {
+    /// Block detectors only match at the start of a line
+    pub fn is_block(&self) -> bool {
+        match self {
+            Self::MarkdownDetector(markdown_detector) => markdown_detector.is_block(),
+            _ => false
+        }
+    }
+
+    /// Whether the detector is tried at the start of a line in the block phase
+    pub fn in_block_phase(&self) -> bool {
+        match self {
+            Self::HTMLDetector(html_detector) => html_detector.is_block_level(),
+            _ => self.is_block()
+        }
+    }
+
+    /// Whether the detector is tried at every position of a leaf in the inline phase
+    pub fn in_inline_phase(&self) -> bool {
+        !self.is_block()
+    }
+
+    /// Block phase: detects the block at the front of the text and advances the text past it
+    pub fn detect_block(&self, queue: &mut &[Token]) -> Option<Result> {
+        match self {
+            Self::MarkdownDetector(markdown_detector) => markdown_detector.detect_block(queue),
+            // HTML blocks start with a tag (after optional whitespace)
+            Self::HTMLDetector(_) if queue.iter().find(|token| !token.is_whitespace()) != Some(&'<') => None,
+            _ => {
+                // The other detectors work on a queue of their own, it only holds the front of the
+                // text and is grown until the block fits (instead of copying the whole text)
+                let mut window = BLOCK_WINDOW.min(queue.len());
+
+                loop {
+                    let mut copy = queue[..window].to_vec();
+
+                    match self.detect(&mut copy) {
+                        // A block that takes the whole window may continue after it
+                        Some(result) if !copy.is_empty() || window == queue.len() => {
+                            *queue = &queue[window - copy.len()..];
+
+                            return Some(result);
+                        },
+                        _ if window == queue.len() => return None,
+                        _ => window = (window * 2).min(queue.len())
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether a block detector may match the queue directly after a line of paragraph text
+    pub fn can_interrupt_paragraph(&self, queue: &[Token]) -> bool {
+        match self {
+            Self::MarkdownDetector(markdown_detector) => markdown_detector.can_interrupt_paragraph(queue),
+            _ => true
+        }
+    }
+
+    /// Whether the content of a result is parsed as blocks again (or only inline otherwise)
+    pub fn has_block_content(&self) -> bool {
+        match self {
+            Self::HTMLDetector(html_detector) => *html_detector == HTMLDetector::DivDetector,
+            Self::MarkdownDetector(markdown_detector) => markdown_detector.has_block_content(),
+            _ => false
+        }
+    }
+}
+
 impl PartialEq for Detector {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -93,6 +267,9 @@ impl PartialEq for Detector {
             (Self::BooleanDetector(boolean_detector_1), Self::BooleanDetector(boolean_detector_2)) => boolean_detector_1 == boolean_detector_2,
             (Self::TagDetector(tag_detector_1), Self::TagDetector(tag_detector_2)) => tag_detector_1 == tag_detector_2,
             (Self::TagScopeDetector(tag_scope_detector_1), Self::TagScopeDetector(tag_scope_detector_2)) => tag_scope_detector_1 == tag_scope_detector_2,
+            (Self::HTMLDetector(html_detector_1), Self::HTMLDetector(html_detector_2)) => html_detector_1 == html_detector_2,
+            (Self::MarkdownDetector(markdown_detector_1), Self::MarkdownDetector(markdown_detector_2)) => markdown_detector_1 == markdown_detector_2,
+            (Self::RawDetector, Self::RawDetector) => true,
             (Self::NoneDetector, Self::NoneDetector) => true,
             _ => false
         }
@@ -110,6 +287,7 @@ impl Detectable for Detector {
             Self::TagDetector(tag_detector) => tag_detector.detect(queue),
             Self::TagScopeDetector(tag_scope_detector) => tag_scope_detector.detect(queue),
             Self::HTMLDetector(html_detector) => html_detector.detect(queue),
+            Self::MarkdownDetector(markdown_detector) => markdown_detector.detect(queue),
             Self::RawDetector => None,
             Self::NoneDetector => None
         }
@@ -143,66 +321,186 @@ impl Consumable for Queue {
         }
     }
 
-    // Consume the whole queue, also consuming the content of a rsult and setting it to children
-    fn consume_any(&mut self, detectors: &Vec<Detector>) -> Option<Vec<Result>> {
-        let mut buffer = vec![];
+    // Consume the whole queue in both phases: first the block structure, then the inline content of its leaves
+    fn consume_any(&mut self, detectors: &[Detector]) -> Option<Vec<Result>> {
+        let children = parse_inlines(self.consume_blocks(detectors)?, detectors);
+
+        if !children.is_empty() {
+            Some(children)
+        } else {
+            None
+        }
+    }
+
+    // Block phase: split the queue into blocks, text between them is kept as raw text (or grouped into paragraphs)
+    fn consume_blocks(&mut self, detectors: &[Detector]) -> Option<Vec<Result>> {
+        let paragraph_detector = Detector::MarkdownDetector(MarkdownDetector::ParagraphDetector);
+
+        // Paragraphs are built from the text between the other blocks
+        let grouped = detectors.contains(&paragraph_detector);
+
+        let block_detectors = detectors.iter()
+            .filter(|detector| detector.in_block_phase() && **detector != paragraph_detector)
+            .collect::<Vec<&Detector>>();
+
+        // The blocks are detected on the text behind a cursor, the text between them is taken at once
+        let text = std::mem::take(self);
+
+        let mut position = 0;
+
+        // Start of the text between the blocks and the line it starts on
+        let mut buffer_start = 0;
+        let mut buffer_line: i64 = 0;
 
         let mut children = vec![];
 
-        while self.len() > 0 {
+        // Block detectors are only tried at the start of a line
+        let mut line_start = true;
+
+        // Whether the current line has text and whether the previous line was paragraph text
+        let mut line_text = false;
+        let mut in_paragraph = false;
+
+        // Line of the queue the next token is on (relative to the start of the queue)
+        let mut line: i64 = 0;
+
+        while position < text.len() {
             let mut found: bool = false;
 
-            for detector in detectors {
-                let mut copy = self.clone();
+            if line_start {
+                let rest = &text[position..];
 
-                match detector.detect(&mut copy) {
-                    Some(mut result) => {
-                        // Handle Raw Buffer
-                        found = true;
+                for detector in &block_detectors {
+                    if in_paragraph && !detector.can_interrupt_paragraph(rest) {
+                        continue;
+                    }
 
-                        if buffer.len() > 0 {
-                            children.push(
-                                Result::new(
-                                    Detector::RawDetector,
-                                    Some(buffer.clone()),
-                                    None,
-                                    None
-                                )
-                            );
-
-                            buffer = vec![];
+                    let mut copy = rest;
+
+                    if let Some(mut result) = detector.detect_block(&mut copy) {
+                        let consumed = rest.len() - copy.len();
+
+                        if consumed == 0 {
+                            continue;
                         }
 
-                        // Consume from the queue
-                        let consumed = self.len() - copy.len();
+                        found = true;
 
-                        for _ in 0..consumed {
-                            self.remove(0);
+                        // Handle Raw Buffer
+                        if buffer_start < position {
+                            children.extend(text_results(text[buffer_start..position].to_vec(), buffer_line, grouped));
                         }
 
+                        // A block may end in the middle of a line (like a closing HTML tag)
+                        line_start = rest[consumed - 1] == '\n';
+
+                        line_text = false;
+                        in_paragraph = false;
+
+                        // Some detectors (like the HTML ones) take the blank lines before the block
+                        let blank_lines = rest[..consumed].iter()
+                            .take_while(|token| token.is_whitespace())
+                            .filter(|token| **token == '\n')
+                            .count() as i64;
+
+                        result.set_property("line", Value::Integer(line + blank_lines));
+
+                        line += rest[..consumed].iter().filter(|token| **token == '\n').count() as i64;
+
+                        position += consumed;
+                        buffer_start = position;
+
                         // Get result content
-                        match result.clone().content {
-                            Some(content) => {
-                                // If content is not empty, consume it recursively
-                                let mut content_queue = content.clone();
-
-                                if content_queue.len() > 0 {
-                                    let subchildren = content_queue.consume_any(detectors);
-
-                                    result.children = match subchildren {
-                                        Some(subchildren) => Some(subchildren),
-                                        None => None
-                                    };
-                                }
-                            },
-                            None => {}
-                        }
+                        result.parse_blocks(detectors);
 
-                        children.push(result.clone());
+                        children.push(result);
 
                         break;
-                    },
-                    None => {}
+                    }
+                }
+            }
+
+            if !found {
+                let token = text[position];
+
+                if buffer_start == position {
+                    buffer_line = line;
+                }
+
+                position += 1;
+
+                line_start = token == '\n';
+
+                if line_start {
+                    line += 1;
+
+                    in_paragraph = line_text;
+                    line_text = false;
+                } else if !token.is_whitespace() {
+                    line_text = true;
+                }
+            }
+        }
+
+        if buffer_start < text.len() {
+            children.extend(text_results(text[buffer_start..].to_vec(), buffer_line, grouped));
+        }
+
+        if !children.is_empty() {
+            Some(children)
+        } else {
+            None
+        }
+    }
+
+    // Inline phase: try every inline detector at every position, also consuming the content of a result and setting it to children
+    fn consume_inlines(&mut self, detectors: &[Detector]) -> Option<Vec<Result>> {
+        let inline_detectors = detectors.iter()
+            .filter(|detector| detector.in_inline_phase())
+            .collect::<Vec<&Detector>>();
+
+        let mut buffer = vec![];
+
+        let mut children = vec![];
+
+        while !self.is_empty() {
+            let mut found: bool = false;
+
+            for detector in &inline_detectors {
+                let mut copy = self.clone();
+
+                if let Some(mut result) = detector.detect(&mut copy) {
+                    let consumed = self.len() - copy.len();
+
+                    if consumed == 0 {
+                        continue;
+                    }
+
+                    // Handle Raw Buffer
+                    found = true;
+
+                    if !buffer.is_empty() {
+                        children.push(
+                            Result::new(
+                                Detector::RawDetector,
+                                Some(buffer.clone()),
+                                None,
+                                None
+                            )
+                        );
+
+                        buffer = vec![];
+                    }
+
+                    // Consume from the queue
+                    self.drain(0..consumed);
+
+                    // Get result content
+                    result.parse_inlines(detectors);
+
+                    children.push(result);
+
+                    break;
                 }
             }
 
@@ -211,7 +509,7 @@ impl Consumable for Queue {
             }
         }
 
-        if buffer.len() > 0 {
+        if !buffer.is_empty() {
             children.push(
                 Result::new(
                     Detector::RawDetector,
@@ -222,7 +520,7 @@ impl Consumable for Queue {
             );
         }
 
-        if children.len() > 0 {
+        if !children.is_empty() {
             Some(children)
         } else {
             None
@@ -236,4 +534,74 @@ impl Consumable for Queue {
     fn to_string(&self) -> String {
         self.iter().collect::<String>()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn detectors() -> Vec<Detector> {
+        vec![
+            Detector::MarkdownDetector(MarkdownDetector::HeadingDetector),
+            Detector::HTMLDetector(HTMLDetector::DivDetector),
+            Detector::MarkdownDetector(MarkdownDetector::EmphasisDetector)
+        ]
+    }
+
+    #[test]
+    fn test_block_phase() {
+        let mut queue = Queue::from_string("# *a*\ntext *b*\n<div>\n# c\n</div>".to_string());
+
+        let blocks = queue.consume_blocks(&detectors()).unwrap();
+
+        assert_eq!(blocks.len(), 3);
+
+        // Leaves are not parsed yet, text between blocks stays raw
+        assert_eq!(blocks[0].get_property("line"), Value::Integer(0));
+        assert_eq!(blocks[0].children, None);
+        assert_eq!(blocks[1].detector, Detector::RawDetector);
+        assert_eq!(blocks[2].detector, Detector::HTMLDetector(HTMLDetector::DivDetector));
+        assert_eq!(blocks[2].get_property("line"), Value::Integer(2));
+
+        // Blank lines taken by a detector are not part of the block
+        let spaced = Queue::from_string("text\n\n<div>\n# c\n</div>".to_string()).consume_blocks(&detectors()).unwrap();
+
+        assert_eq!(spaced[1].get_property("line"), Value::Integer(2));
+
+        // The content of the div is parsed as blocks
+        let children = blocks[2].children.clone().unwrap();
+
+        assert_eq!(children[1].get_property("tag"), Value::String("h1".to_string()));
+    }
+
+    #[test]
+    fn test_block_window() {
+        // The window grows until the div is closed
+        let source = format!("<div>\n{}\n</div>\nafter", "a".repeat(BLOCK_WINDOW * 2));
+
+        let blocks = Queue::from_string(source).consume_blocks(&detectors()).unwrap();
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].detector, Detector::HTMLDetector(HTMLDetector::DivDetector));
+        assert_eq!(blocks[1].content, Some(Queue::from_string("\nafter".to_string())));
+    }
+
+    #[test]
+    fn test_inline_phase() {
+        let mut queue = Queue::from_string("# *a*\ntext *b* <div># c</div>".to_string());
+
+        let results = queue.consume_any(&detectors()).unwrap();
+
+        assert_eq!(results.len(), 6);
+        assert_eq!(results[0].children.clone().unwrap()[0].detector, Detector::MarkdownDetector(MarkdownDetector::EmphasisDetector));
+        assert_eq!(results[1].content, Some(Queue::from_string("text ".to_string())));
+        assert_eq!(results[2].detector, Detector::MarkdownDetector(MarkdownDetector::EmphasisDetector));
+
+        // A div in the middle of a line is found in the inline phase, its content is still parsed as blocks
+        let children = results[5].children.clone().unwrap();
+
+        assert_eq!(results[5].detector, Detector::HTMLDetector(HTMLDetector::DivDetector));
+        assert_eq!(children[0].get_property("tag"), Value::String("h1".to_string()));
+    }
+}
@@ -19,11 +19,8 @@ impl Detectable for BooleanDetector {
                 for detector in detectors {
                     let mut queue_clone = queue.clone();
 
-                    match queue_clone.consume(detector) {
-                        (true, _, Some(result)) => {
-                            return Some(result);
-                        },
-                        _ => {}
+                    if let (true, _, Some(result)) = queue_clone.consume(detector) {
+                        return Some(result);
                     }
                 }
 
@@ -49,11 +46,8 @@ impl Detectable for BooleanDetector {
                 for detector in detectors {
                     let mut queue_clone = queue.clone();
 
-                    match queue_clone.consume(detector) {
-                        (true, _, Some(_)) => {
-                            return None;
-                        },
-                        _ => {}
+                    if let (true, _, Some(_)) = queue_clone.consume(detector) {
+                        return None;
                     }
                 }
 
@@ -64,11 +58,8 @@ impl Detectable for BooleanDetector {
             BooleanDetector::Not(detector) => {
                 let mut queue_clone = queue.clone();
 
-                match queue_clone.consume(detector) {
-                    (true, _, Some(_)) => {
-                        return None;
-                    },
-                    _ => {}
+                if let (true, _, Some(_)) = queue_clone.consume(detector) {
+                    return None;
                 }
 
                 let result = Result::new(Detector::BooleanDetector(self.clone()), Some(queue.clone()), None, None);
@@ -22,11 +22,10 @@ pub struct NumberDetector {
 
 impl PartialEq for NumberType {
     fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (NumberType::Integer, NumberType::Integer) => true,
-            (NumberType::Float, NumberType::Float) => true,
-            _ => false
-        }
+        matches!(
+            (self, other),
+            (NumberType::Integer, NumberType::Integer) | (NumberType::Float, NumberType::Float)
+        )
     }
 }
 
@@ -43,9 +42,7 @@ impl Detectable for NumberDetector {
     fn detect(&self, queue: &mut Queue) -> Option<Result> {
         let mut inner: Queue = vec![];
 
-        let factor;
-
-        if queue[0] == '-' {
+        let factor = if queue[0] == '-' {
             if self.positive.unwrap_or(false) {
                 return None;
             }
@@ -54,10 +51,10 @@ impl Detectable for NumberDetector {
                 queue.remove(0)
             );
 
-            factor = -1;
+            -1
         } else {
-            factor = 1;
-        }
+            1
+        };
 
         let found_type;
 
@@ -72,7 +69,7 @@ impl Detectable for NumberDetector {
                         while !queue.is_empty() {
                             let queue_char = queue[0];
 
-                            if queue_char.is_digit(10) {
+                            if queue_char.is_ascii_digit() {
                                 found = true;
 
                                 inner.push(
@@ -93,13 +90,7 @@ impl Detectable for NumberDetector {
                         while !queue.is_empty() {
                             let queue_char = queue[0];
 
-                            if queue_char.is_digit(10) {
-                                found = true;
-
-                                inner.push(
-                                    queue.remove(0)
-                                );
-                            } else if queue_char == '.' {
+                            if queue_char.is_ascii_digit() || queue_char == '.' {
                                 found = true;
 
                                 inner.push(
@@ -123,7 +114,7 @@ impl Detectable for NumberDetector {
                 while !queue.is_empty() {
                     let queue_char = queue[0];
 
-                    if queue_char.is_digit(10) {
+                    if queue_char.is_ascii_digit() {
                         found = true;
 
                         inner.push(
@@ -231,7 +222,7 @@ mod tests {
 
         let (matched, _, result) = queue.consume(&Detector::NumberDetector(number_detector.clone()));
 
-        assert_eq!(matched, true);
+        assert!(matched);
         match result {
             Some(result) => {
                 assert_eq!(result.properties, Some(Dict::from_values(vec![
@@ -248,7 +239,7 @@ mod tests {
 
         let (matched, _, result) = queue.consume(&Detector::NumberDetector(number_detector.clone()));
 
-        assert_eq!(matched, true);
+        assert!(matched);
         match result {
             Some(result) => {
                 assert_eq!(result.properties, Some(Dict::from_values(vec![
@@ -273,7 +264,7 @@ mod tests {
 
         let (matched, _, result) = queue.consume(&Detector::NumberDetector(number_detector));
 
-        assert_eq!(matched, true);
+        assert!(matched);
         match result {
             Some(result) => {
                 assert_eq!(result.properties, Some(Dict::from_values(vec![
@@ -298,7 +289,7 @@ mod tests {
 
         let (matched, _, result) = queue.consume(&Detector::NumberDetector(number_detector.clone()));
 
-        assert_eq!(matched, true);
+        assert!(matched);
         match result {
             Some(result) => {
                 assert_eq!(result.properties, Some(Dict::from_values(vec![
@@ -315,7 +306,7 @@ mod tests {
 
         let (matched, _, result) = queue.consume(&Detector::NumberDetector(number_detector.clone()));
 
-        assert_eq!(matched, false);
+        assert!(!matched);
         assert_eq!(result, None);
     }
 
@@ -330,7 +321,7 @@ mod tests {
 
         let (matched, _, result) = queue.consume(&Detector::NumberDetector(number_detector.clone()));
 
-        assert_eq!(matched, true);
+        assert!(matched);
         match result {
             Some(result) => {
                 assert_eq!(result.properties, Some(Dict::from_values(vec![
@@ -347,7 +338,7 @@ mod tests {
 
         let (matched, _, result) = queue.consume(&Detector::NumberDetector(number_detector.clone()));
 
-        assert_eq!(matched, false);
+        assert!(!matched);
         assert_eq!(result, None);
     }
 }
\ No newline at end of file
@@ -209,7 +209,7 @@ mod tests {
 
         let (matched, _, result) = queue.consume(&Detector::PropertyDetector(PropertyDetector::new(None, Some(true))));
 
-        assert_eq!(matched, true);
+        assert!(matched);
 
         match result {
             Some(result) => {
@@ -231,21 +231,21 @@ mod tests {
 
         let (matched, _, result) = queue.consume(&Detector::PropertyDetector(PropertyDetector::new(None, Some(true))));
 
-        assert_eq!(matched, false);
+        assert!(!matched);
         assert_eq!(result, None);
 
         let mut queue = Queue::from_string("key = \"value".to_string());
 
         let (matched, _, result) = queue.consume(&Detector::PropertyDetector(PropertyDetector::new(None, Some(true))));
 
-        assert_eq!(matched, false);
+        assert!(!matched);
         assert_eq!(result, None);
 
         let mut queue = Queue::from_string("key = value\"".to_string());
 
         let (matched, _, result) = queue.consume(&Detector::PropertyDetector(PropertyDetector::new(None, Some(true))));
 
-        assert_eq!(matched, false);
+        assert!(!matched);
         assert_eq!(result, None);
 
         // JSON
@@ -253,7 +253,7 @@ mod tests {
 
         let (matched, _, result) = queue.consume(&Detector::PropertyDetector(PropertyDetector::new(None, Some(true))));
 
-        assert_eq!(matched, false);
+        assert!(!matched);
         assert_eq!(result, None);
     }   
 
@@ -263,7 +263,7 @@ mod tests {
 
         let (matched, _, result) = queue.consume(&Detector::PropertyDetector(PropertyDetector::new(Some(true), None)));
 
-        assert_eq!(matched, true);
+        assert!(matched);
 
         match result {
             Some(result) => {
@@ -285,21 +285,21 @@ mod tests {
 
         let (matched, _, result) = queue.consume(&Detector::PropertyDetector(PropertyDetector::new(Some(true), None)));
 
-        assert_eq!(matched, false);
+        assert!(!matched);
         assert_eq!(result, None);
 
         let mut queue = Queue::from_string("\"key\" : \"value".to_string());
 
         let (matched, _, result) = queue.consume(&Detector::PropertyDetector(PropertyDetector::new(Some(true), None)));
 
-        assert_eq!(matched, false);
+        assert!(!matched);
         assert_eq!(result, None);
 
         let mut queue = Queue::from_string("\"key\" : value\"".to_string());
 
         let (matched, _, result) = queue.consume(&Detector::PropertyDetector(PropertyDetector::new(Some(true), None)));
 
-        assert_eq!(matched, false);
+        assert!(!matched);
         assert_eq!(result, None);
 
         // HTML
@@ -307,7 +307,7 @@ mod tests {
 
         let (matched, _, result) = queue.consume(&Detector::PropertyDetector(PropertyDetector::new(Some(true), None)));
 
-        assert_eq!(matched, false);
+        assert!(!matched);
         assert_eq!(result, None);
     }
 }
\ No newline at end of file
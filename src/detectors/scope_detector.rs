@@ -26,14 +26,10 @@ impl ScopeDetector {
 impl Detectable for ScopeDetector {
     fn detect(&self, queue: &mut Queue) -> Option<Result> {
         let mut inner: Queue = vec![];
-        let start_result;
-
-        match queue.consume(self.start.as_ref()) {
-            (true, _, Some(result)) => {
-                start_result = result;
-            },
+        let start_result = match queue.consume(self.start.as_ref()) {
+            (true, _, Some(result)) => result,
             _ => return None
-        }
+        };
 
         let mut layer = 1;
 
@@ -121,7 +117,7 @@ mod tests {
 
         let (matched, _, result) = queue.consume(&detector);
 
-        assert_eq!(matched, true);
+        assert!(matched);
 
         match result {
             Some(result) => {
@@ -144,7 +140,7 @@ mod tests {
 
         let (matched, _, result) = queue.consume(&detector);
 
-        assert_eq!(matched, true);
+        assert!(matched);
 
         match result {
             Some(result) => {
@@ -167,7 +163,7 @@ mod tests {
 
         let (matched, _, result) = queue.consume(&detector);
 
-        assert_eq!(matched, true);
+        assert!(matched);
 
         match result {
             Some(result) => {
@@ -143,33 +143,33 @@ mod tests {
 
         let (matched, word, _) = queue.clone().consume(&Detector::WordDetector(WordDetector::new(Some("Hello".to_string()), None, None)));
 
-        assert_eq!(matched, true);
+        assert!(matched);
         assert_eq!(word.unwrap(), "Hello".to_string());
 
         let (matched, _, _) = queue.clone().consume(&Detector::WordDetector(WordDetector::new(Some("World".to_string()), None, None)));
 
-        assert_eq!(matched, false);
+        assert!(!matched);
 
         let (matched, word, _) = queue.clone().consume(&Detector::WordDetector(WordDetector::new(None, Some(VALID_IDENTIFIER_TOKENS.to_vec()), None)));
 
-        assert_eq!(matched, true);
+        assert!(matched);
         assert_eq!(word.unwrap(), "Hello".to_string());
 
         let queue = Queue::from_string("   ".to_string());
 
         let (matched, word, _) = queue.clone().consume(&Detector::WordDetector(WordDetector::new(None, Some(WHITESPACES.to_vec()), None)));
 
-        assert_eq!(matched, true);
+        assert!(matched);
         assert_eq!(word.unwrap(), "   ".to_string());
 
         let queue = Queue::from_string("".to_string());
 
         let (matched, _, _) = queue.clone().consume(&Detector::WordDetector(WordDetector::new(None, Some(WHITESPACES.to_vec()), None)));
 
-        assert_eq!(matched, false);
+        assert!(!matched);
 
         let (matched, _, _) = queue.clone().consume(&Detector::WordDetector(WordDetector::new(None, Some(VALID_IDENTIFIER_TOKENS.to_vec()), None)));
 
-        assert_eq!(matched, false);
+        assert!(!matched);
     }
 }
\ No newline at end of file
@@ -31,16 +31,42 @@ pub enum HTMLDetector {
     HeadingDetector
 }
 
+impl HTMLDetector {
+    /// Block level tags are also detected at the start of a line in the block phase
+    pub fn is_block_level(&self) -> bool {
+        match self {
+            HTMLDetector::DivDetector => true,
+            HTMLDetector::SpanDetector => false,
+            HTMLDetector::ImgDetector => false,
+            HTMLDetector::LinkDetector => false,
+            HTMLDetector::ParagraphDetector => true,
+            HTMLDetector::HeadingDetector => true
+        }
+    }
+}
+
 impl Detectable for HTMLDetector {
     fn detect(&self, queue: &mut Queue) -> Option<Result> {
-        match self {
+        // Elements start with a tag (after optional whitespace), this avoids building the tag detectors
+        if queue.iter().find(|token| !token.is_whitespace()) != Some(&'<') {
+            return None;
+        }
+
+        let result = match self {
             HTMLDetector::DivDetector => div_detector().detect(queue),
             HTMLDetector::SpanDetector => span_detector().detect(queue),
             HTMLDetector::ImgDetector => img_detector().detect(queue),
             HTMLDetector::LinkDetector => a_detector().detect(queue),
             HTMLDetector::ParagraphDetector => p_detector().detect(queue),
             HTMLDetector::HeadingDetector => h_detector().detect(queue)
-        }
+        };
+
+        // The results know which HTML detector found them (e.g. for block content of divs)
+        result.map(|mut result| {
+            result.detector = Detector::HTMLDetector(self.clone());
+
+            result
+        })
     }
 }
 
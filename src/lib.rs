@@ -6,45 +6,397 @@ pub mod types;
 pub mod detectors;
 pub mod advanced_detectors;
 pub mod html;
+pub mod markdown;
 
 use html::HTMLDetector;
+use markdown::MarkdownDetector;
+use markdown::emphasis::resolve_emphasis;
+use markdown::list::collect_tasks;
+use markdown::reference::{LinkReferences, collect_definitions};
+use markdown::autolink::linkify;
+use markdown::escape::decode_references;
+use markdown::footnote::{Footnotes, collect_footnotes, resolve_footnotes};
+use markdown::inline_extension::InlineExtensions;
+use markdown::front_matter::extract_front_matter;
+use markdown::callout::CalloutKinds;
+use markdown::wikilink::resolve_wikilinks;
+use markdown::include::{PageLoader, IncludeError, DEFAULT_MAX_DEPTH, resolve_includes};
+use markdown::template::{TemplateRegistry, TemplateError, expand_templates};
+use pyo3::exceptions::{PyValueError, PyRecursionError};
 use pyo3::prelude::*;
+use std::collections::HashMap;
 
-use types::{Queue, Value};
+use types::{Queue, Value, Dict};
 use crate::base::*;
 
-#[pyfunction]
-fn parse(input: &str) -> PyResult<PyObject> {
-    let mut consumable: Queue = Consumable::from_string(input.to_string());
-
-    let result = consumable.consume_any(
-        &vec![
-            Detector::HTMLDetector(HTMLDetector::DivDetector),
-            Detector::HTMLDetector(HTMLDetector::ParagraphDetector),
-            Detector::HTMLDetector(HTMLDetector::ImgDetector),
-            Detector::HTMLDetector(HTMLDetector::LinkDetector),
-            Detector::HTMLDetector(HTMLDetector::HeadingDetector),
-            Detector::HTMLDetector(HTMLDetector::SpanDetector)
-        ]
+/// Options of `parse` (all optional features are disabled by default)
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Turn bare URLs and e-mail addresses into links
+    pub linkify: bool,
+    /// Keep backslash escapes and character references in their source form
+    pub keep_source: bool,
+    /// Return the content together with the diagnostics (like undefined footnotes)
+    pub diagnostics: bool,
+    /// Extract a leading YAML or TOML front matter block and return it as `meta`
+    pub front_matter: bool,
+    /// Detect inline (`$...$`) and display (`$$...$$`) math
+    pub math: bool,
+    /// Detect callouts (`> [!NOTE]` and `::: kind`) of the allowed kinds
+    pub callouts: Option<CalloutKinds>,
+    /// Detect wiki links (`[[Page#Section|label]]`)
+    pub wikilinks: bool,
+    /// Detect include directives (`{{include:Page}}`)
+    pub includes: bool,
+    /// The maximum depth of nested includes when they are resolved (`DEFAULT_MAX_DEPTH` if not set)
+    pub max_include_depth: Option<usize>,
+    /// Detect template invocations (`{{Name|argument|key=value}}`)
+    pub templates: bool,
+    /// Expand the invocations of these templates before parsing
+    pub template_registry: Option<TemplateRegistry>,
+    /// The enabled inline extensions (`~~del~~`, `==mark==`, `^sup^` and `~sub~`)
+    pub extensions: InlineExtensions
+}
+
+/// A parsed document
+#[derive(Debug, Clone, Default)]
+pub struct Document {
+    pub content: Option<Vec<Result>>,
+    pub meta: Dict,
+    pub diagnostics: Vec<Value>
+}
+
+impl Document {
+    /// The content, or a dict with `content` and `meta` and/or `diagnostics` if they are enabled
+    pub fn into_value(self, options: &Options) -> Value {
+        if !options.front_matter && !options.diagnostics && self.content.is_none() {
+            return Value::NoneValue;
+        }
+
+        // A document without blocks has empty content
+        let content = Value::Array(
+            self.content.unwrap_or_default().into_iter().map(Value::Result).collect::<Vec<Value>>()
+        );
+
+        if !options.front_matter && !options.diagnostics {
+            return content;
+        }
+
+        let mut document = Dict::from_values(vec![("content".to_string(), content)]);
+
+        if options.front_matter {
+            document.set("meta", Value::Dict(self.meta));
+        }
+
+        if options.diagnostics {
+            document.set("diagnostics", Value::Array(self.diagnostics));
+        }
+
+        Value::Dict(document)
+    }
+}
+
+/// The detectors used to parse a document (block detectors are only used in the block phase)
+fn detectors(options: &Options) -> Vec<Detector> {
+    let mut detectors = vec![
+        Detector::MarkdownDetector(MarkdownDetector::IndentedCodeDetector),
+        Detector::MarkdownDetector(MarkdownDetector::FencedCodeDetector),
+        Detector::MarkdownDetector(MarkdownDetector::FootnoteDefinitionDetector),
+        Detector::MarkdownDetector(MarkdownDetector::LinkDefinitionDetector),
+        Detector::MarkdownDetector(MarkdownDetector::BlockquoteDetector),
+        Detector::MarkdownDetector(MarkdownDetector::ThematicBreakDetector),
+        Detector::MarkdownDetector(MarkdownDetector::TableDetector),
+        Detector::MarkdownDetector(MarkdownDetector::ListDetector),
+        Detector::MarkdownDetector(MarkdownDetector::HeadingDetector),
+        Detector::MarkdownDetector(MarkdownDetector::SetextHeadingDetector),
+        Detector::MarkdownDetector(MarkdownDetector::ParagraphDetector),
+        Detector::MarkdownDetector(MarkdownDetector::EscapeDetector),
+        Detector::MarkdownDetector(MarkdownDetector::CodeSpanDetector),
+        Detector::MarkdownDetector(MarkdownDetector::AutolinkDetector),
+        Detector::MarkdownDetector(MarkdownDetector::LineBreakDetector),
+        Detector::MarkdownDetector(MarkdownDetector::SoftBreakDetector),
+        Detector::MarkdownDetector(MarkdownDetector::FootnoteReferenceDetector),
+        Detector::MarkdownDetector(MarkdownDetector::ImageDetector),
+        Detector::MarkdownDetector(MarkdownDetector::LinkDetector),
+        Detector::HTMLDetector(HTMLDetector::DivDetector),
+        Detector::HTMLDetector(HTMLDetector::ParagraphDetector),
+        Detector::HTMLDetector(HTMLDetector::ImgDetector),
+        Detector::HTMLDetector(HTMLDetector::LinkDetector),
+        Detector::HTMLDetector(HTMLDetector::HeadingDetector),
+        Detector::HTMLDetector(HTMLDetector::SpanDetector),
+        Detector::MarkdownDetector(MarkdownDetector::EmphasisDetector)
+    ];
+
+    if options.math {
+        // Math is verbatim like code
+        insert_after(&mut detectors, MarkdownDetector::FencedCodeDetector, MarkdownDetector::MathBlockDetector);
+        insert_after(&mut detectors, MarkdownDetector::CodeSpanDetector, MarkdownDetector::MathDetector);
+    }
+
+    if let Some(kinds) = &options.callouts {
+        // Callouts in the blockquote form are tried before blockquotes
+        let position = detectors.iter()
+            .position(|detector| *detector == Detector::MarkdownDetector(MarkdownDetector::BlockquoteDetector))
+            .unwrap_or(detectors.len());
+
+        detectors.insert(position, Detector::MarkdownDetector(MarkdownDetector::CalloutDetector(kinds.clone())));
+    }
+
+    if options.includes {
+        insert_after(&mut detectors, MarkdownDetector::FencedCodeDetector, MarkdownDetector::IncludeDetector);
+    }
+
+    if options.templates {
+        insert_after(&mut detectors, MarkdownDetector::AutolinkDetector, MarkdownDetector::TemplateDetector);
+    }
+
+    if options.wikilinks {
+        // `[[...]]` would be a link or a footnote reference otherwise
+        let position = detectors.iter()
+            .position(|detector| *detector == Detector::MarkdownDetector(MarkdownDetector::FootnoteReferenceDetector))
+            .unwrap_or(detectors.len());
+
+        detectors.insert(position, Detector::MarkdownDetector(MarkdownDetector::WikilinkDetector));
+    }
+
+    if !options.extensions.is_empty() {
+        detectors.push(Detector::MarkdownDetector(MarkdownDetector::InlineExtensionDetector(options.extensions.clone())));
+    }
+
+    detectors
+}
+
+/// Inserts a detector after another one (or at the end if it isn't in the list)
+fn insert_after(detectors: &mut Vec<Detector>, after: MarkdownDetector, detector: MarkdownDetector) {
+    let position = detectors.iter()
+        .position(|other| *other == Detector::MarkdownDetector(after.clone()))
+        .map_or(detectors.len(), |position| position + 1);
+
+    detectors.insert(position, Detector::MarkdownDetector(detector));
+}
+
+/// Parses a document into the result tree
+///
+/// The block structure is parsed first. Link reference and footnote definitions are collected
+/// from it (they can appear anywhere in the document) before the inline content is parsed, so
+/// that references can be resolved against them. With a loader, included pages are parsed
+/// into the block structure as well.
+fn parse_document(input: &str, options: &Options, loader: Option<&mut dyn PageLoader<PyErr>>) -> PyResult<Document> {
+    let mut document = Document::default();
+
+    let input = if options.front_matter {
+        let (meta, content) = extract_front_matter(input, &mut document.diagnostics);
+
+        document.meta = meta;
+
+        content
+    } else {
+        input.to_string()
+    };
+
+    let input = match &options.template_registry {
+        Some(registry) => expand_templates(&input, registry).map_err(template_error)?,
+        None => input
+    };
+
+    let mut consumable: Queue = Consumable::from_string(input);
+
+    let mut detectors = detectors(options);
+
+    let mut blocks = match consumable.consume_blocks(&detectors) {
+        Some(blocks) => blocks,
+        None => return Ok(document)
+    };
+
+    if let Some(loader) = loader {
+        // The front matter of included pages is dropped, their templates are expanded
+        let mut load_page = |page: &str| -> PyResult<String> {
+            let source = loader.load(page)?;
+
+            let source = if options.front_matter {
+                extract_front_matter(&source, &mut vec![]).1
+            } else {
+                source
+            };
+
+            match &options.template_registry {
+                Some(registry) => expand_templates(&source, registry).map_err(template_error),
+                None => Ok(source)
+            }
+        };
+
+        let parse_page = |source: &str| {
+            Queue::from_string(source.to_string()).consume_blocks(&detectors).unwrap_or_default()
+        };
+
+        let max_depth = options.max_include_depth.unwrap_or(DEFAULT_MAX_DEPTH);
+
+        blocks = resolve_includes(blocks, &mut load_page, &parse_page, max_depth).map_err(include_error)?;
+    }
+
+    let mut references = LinkReferences::new();
+
+    collect_definitions(&mut blocks, &mut references);
+
+    let mut footnotes = Footnotes::new();
+
+    collect_footnotes(&mut blocks, &mut footnotes, &mut document.diagnostics);
+
+    if !references.is_empty() {
+        // Reference links are tried after inline links
+        insert_after(&mut detectors, MarkdownDetector::LinkDetector, MarkdownDetector::ReferenceDetector(references));
+    }
+
+    let results = parse_inlines(blocks, &detectors);
+
+    for (_, definition) in footnotes.definitions.iter_mut() {
+        definition.children = definition.children.take().map(|children| parse_inlines(children, &detectors));
+    }
+
+    let results = resolve_footnotes(results, footnotes, &mut document.diagnostics);
+
+    let results = resolve_emphasis(results);
+
+    let results = if options.linkify { linkify(results) } else { results };
+
+    document.content = Some(
+        if options.keep_source { results } else { decode_references(results) }
     );
 
-    let result_value = match result {
-        Some(result) => Value::Array(
-            result.iter().map(|result| Value::Result(result.clone())).collect::<Vec<Value>>()
-        ),
-        None => Value::NoneValue
+    Ok(document)
+}
+
+fn template_error(error: TemplateError) -> PyErr {
+    match error {
+        TemplateError::Cycle(_) => PyValueError::new_err(error.to_string()),
+        TemplateError::TooDeep(_) => PyRecursionError::new_err(error.to_string())
+    }
+}
+
+fn include_error(error: IncludeError<PyErr>) -> PyErr {
+    match error {
+        IncludeError::Loader(error) => error,
+        IncludeError::Cycle(_) => PyValueError::new_err(error.to_string()),
+        IncludeError::TooDeep(_) => PyRecursionError::new_err(error.to_string())
+    }
+}
+
+#[pyfunction(
+    linkify = "false",
+    keep_source = "false",
+    diagnostics = "false",
+    front_matter = "false",
+    math = "false",
+    callouts = "false",
+    callout_kinds = "None",
+    wikilinks = "false",
+    pages = "None",
+    includes = "false",
+    loader = "None",
+    max_include_depth = "None",
+    templates = "false",
+    template_registry = "None",
+    strikethrough = "false",
+    highlight = "false",
+    superscript = "false",
+    subscript = "false"
+)]
+#[allow(clippy::too_many_arguments)]
+fn parse(
+    input: &str,
+    linkify: bool,
+    keep_source: bool,
+    diagnostics: bool,
+    front_matter: bool,
+    math: bool,
+    callouts: bool,
+    callout_kinds: Option<Vec<String>>,
+    wikilinks: bool,
+    pages: Option<PyObject>,
+    includes: bool,
+    loader: Option<PyObject>,
+    max_include_depth: Option<usize>,
+    templates: bool,
+    template_registry: Option<HashMap<String, String>>,
+    strikethrough: bool,
+    highlight: bool,
+    superscript: bool,
+    subscript: bool
+) -> PyResult<PyObject> {
+    let options = Options {
+        linkify,
+        keep_source,
+        diagnostics,
+        front_matter,
+        math,
+        callouts: if callouts {
+            Some(callout_kinds.map_or_else(CalloutKinds::default, CalloutKinds::new))
+        } else {
+            None
+        },
+        wikilinks,
+        includes,
+        max_include_depth,
+        templates,
+        template_registry: template_registry.map(|templates| TemplateRegistry { templates }),
+        extensions: InlineExtensions {
+            strikethrough,
+            highlight,
+            superscript,
+            subscript
+        }
+    };
+
+    let py_gil = Python::acquire_gil();
+
+    let py = py_gil.python();
+
+    // The loader returns the source of a page
+    let mut load_page = loader.map(|loader| move |page: &str| -> PyResult<String> {
+        loader.as_ref(py).call1((page,))?.extract::<String>()
+    });
+
+    let mut document = parse_document(input, &options, load_page.as_mut().map(|loader| loader as &mut dyn PageLoader<PyErr>))?;
+
+    if let Some(pages) = pages {
+        let pages = pages.as_ref(py);
+
+        // A callable is asked for each page, anything else has to support `in` (like a set)
+        let mut exists = |target: &str| -> PyResult<bool> {
+            if pages.is_callable() {
+                pages.call1((target,))?.is_true()
+            } else {
+                pages.call_method1("__contains__", (target,))?.is_true()
+            }
+        };
+
+        if let Some(content) = document.content.take() {
+            document.content = Some(resolve_wikilinks(content, &mut exists)?);
+        }
+    }
+
+    Ok(document.into_value(&options).into_py(py))
+}
+
+/// Returns all task list items of a document with their text, state and line
+#[pyfunction]
+fn tasks(input: &str) -> PyResult<PyObject> {
+    let tasks = match parse_document(input, &Options::default(), None)?.content {
+        Some(result) => collect_tasks(&result),
+        None => vec![]
     };
 
     let py_gil = Python::acquire_gil();
 
     let py = py_gil.python();
 
-    Ok(result_value.into_py(py))
+    Ok(Value::Array(tasks).into_py(py))
 }
 
 #[pymodule]
 fn pyrustymd(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(parse, m)?)?;
+    m.add_function(wrap_pyfunction!(tasks, m)?)?;
 
     Ok(())
-}
\ No newline at end of file
+}
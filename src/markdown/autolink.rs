@@ -0,0 +1,359 @@
+// Autolink Detector
+// ------------------------
+// Detects URIs and e-mail addresses in angle brackets:
+//
+//     <https://example.com> or <mail@example.com>
+//
+// `linkify` optionally turns bare URLs (`https://`, `http://`, `www.`) and e-mail addresses
+// in raw text into links as well (GFM extended autolinks). The text of an autolink is
+// kept verbatim and never parsed again.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::MarkdownDetector;
+use crate::markdown::link::link_result;
+use crate::types::{Queue, Token, Value};
+
+const MIN_SCHEME_LENGTH: usize = 2;
+const MAX_SCHEME_LENGTH: usize = 32;
+
+fn raw(text: Queue) -> Result {
+    Result::new(Detector::RawDetector, Some(text), None, None)
+}
+
+/// Builds an `a` node with the verbatim text as its only child
+fn autolink_result(text: Queue, destination: Queue) -> Result {
+    let mut result = link_result(MarkdownDetector::AutolinkDetector, vec![], destination, None);
+
+    result.content = None;
+    result.children = Some(vec![raw(text)]);
+
+    result
+}
+
+/// Whether the text is an absolute URI (`scheme:...` without spaces or angle brackets)
+fn is_uri(text: &[Token]) -> bool {
+    let scheme = match text.iter().position(|token| *token == ':') {
+        Some(scheme) => scheme,
+        None => return false
+    };
+
+    (MIN_SCHEME_LENGTH..=MAX_SCHEME_LENGTH).contains(&scheme) &&
+    text[0].is_ascii_alphabetic() &&
+    text[..scheme].iter().all(|token| token.is_ascii_alphanumeric() || ['+', '.', '-'].contains(token)) &&
+    text[scheme + 1..].iter().all(|token| !token.is_whitespace() && !token.is_control() && *token != '<' && *token != '>')
+}
+
+/// Whether the text is an e-mail address as allowed in autolinks
+fn is_email(text: &[Token]) -> bool {
+    let at = match text.iter().position(|token| *token == '@') {
+        Some(at) => at,
+        None => return false
+    };
+
+    let local = &text[..at];
+
+    let local_valid = !local.is_empty() && local.iter().all(|token| {
+        token.is_ascii_alphanumeric() || ".!#$%&'*+/=?^_`{|}~-".contains(*token)
+    });
+
+    let labels = text[at + 1..].split(|token| *token == '.').collect::<Vec<&[Token]>>();
+
+    let domain_valid = labels.iter().all(|label| {
+        !label.is_empty() &&
+        label.len() <= 63 &&
+        label.iter().all(|token| token.is_ascii_alphanumeric() || *token == '-') &&
+        label[0] != '-' &&
+        label[label.len() - 1] != '-'
+    });
+
+    local_valid && domain_valid
+}
+
+pub fn detect_autolink(queue: &mut Queue) -> Option<Result> {
+    if queue.first() != Some(&'<') {
+        return None;
+    }
+
+    let end = queue.iter().position(|token| *token == '>' || *token == '\n')?;
+
+    if queue[end] != '>' {
+        return None;
+    }
+
+    let text = queue[1..end].to_vec();
+
+    let destination = if is_uri(&text) {
+        text.clone()
+    } else if is_email(&text) {
+        let mut destination = Queue::from_string("mailto:".to_string());
+
+        destination.extend(&text);
+
+        destination
+    } else {
+        return None;
+    };
+
+    queue.drain(0..end + 1);
+
+    Some(autolink_result(text, destination))
+}
+
+// Extended Autolinks
+// ------------------------
+
+fn starts_with(text: &[Token], prefix: &str) -> bool {
+    let prefix = prefix.chars().collect::<Queue>();
+
+    text.len() >= prefix.len() && text[..prefix.len()].iter().zip(&prefix).all(|(a, b)| a.eq_ignore_ascii_case(b))
+}
+
+/// Length of a valid domain at the start of the text (at least one period, no underscores in the last two labels)
+fn domain_length(text: &[Token]) -> Option<usize> {
+    let length = text.iter().take_while(|token| token.is_alphanumeric() || ['-', '_', '.'].contains(token)).count();
+
+    // A trailing period is punctuation, not part of the domain
+    let domain = text[..length].iter().collect::<String>();
+    let domain = domain.trim_end_matches('.');
+
+    let labels = domain.split('.').collect::<Vec<&str>>();
+
+    if labels.len() < 2 || labels.iter().any(|label| label.is_empty()) {
+        return None;
+    }
+
+    if labels[labels.len() - 2..].iter().any(|label| label.contains('_')) {
+        return None;
+    }
+
+    Some(domain.chars().count())
+}
+
+/// Removes trailing punctuation, unbalanced closing parentheses and entity references
+fn trim_trailing(link: &[Token]) -> usize {
+    let mut end = link.len();
+
+    loop {
+        match link[..end].last() {
+            Some('?' | '!' | '.' | ',' | ':' | '*' | '_' | '~' | '\'' | '"') => end -= 1,
+            Some(')') => {
+                let open = link[..end].iter().filter(|token| **token == '(').count();
+                let close = link[..end].iter().filter(|token| **token == ')').count();
+
+                if close <= open {
+                    break;
+                }
+
+                end -= 1;
+            },
+            Some(';') => {
+                let name = link[..end - 1].iter().rev().take_while(|token| token.is_ascii_alphanumeric()).count();
+
+                if name == 0 || end < name + 2 || link[end - name - 2] != '&' {
+                    break;
+                }
+
+                end -= name + 2;
+            },
+            _ => break
+        }
+    }
+
+    end
+}
+
+/// Matches `www.`, `http://` or `https://` links, returns the link length and the destination
+fn extended_url(text: &[Token]) -> Option<(usize, Queue)> {
+    let (scheme, prefix) = if starts_with(text, "www.") {
+        ("http://", 0)
+    } else if starts_with(text, "http://") {
+        ("", 7)
+    } else if starts_with(text, "https://") {
+        ("", 8)
+    } else {
+        return None;
+    };
+
+    domain_length(&text[prefix..])?;
+
+    let length = text.iter().take_while(|token| !token.is_whitespace() && **token != '<').count();
+
+    let length = trim_trailing(&text[..length]);
+
+    let mut destination = Queue::from_string(scheme.to_string());
+
+    destination.extend(&text[..length]);
+
+    Some((length, destination))
+}
+
+/// Matches bare e-mail addresses, returns the address length and the destination
+fn extended_email(text: &[Token]) -> Option<(usize, Queue)> {
+    let local = text.iter().take_while(|token| token.is_ascii_alphanumeric() || ['.', '-', '_', '+'].contains(token)).count();
+
+    if local == 0 || text.get(local) != Some(&'@') {
+        return None;
+    }
+
+    let domain = &text[local + 1..];
+
+    let length = domain.iter().take_while(|token| token.is_ascii_alphanumeric() || ['-', '_', '.'].contains(token)).count();
+
+    // A trailing period is not part of the address
+    let length = domain[..length].iter().rposition(|token| *token != '.').map_or(0, |last| last + 1);
+
+    let domain = &domain[..length];
+
+    if !domain.contains(&'.') || domain.last().is_some_and(|token| *token == '-' || *token == '_') {
+        return None;
+    }
+
+    let length = local + 1 + length;
+
+    let mut destination = Queue::from_string("mailto:".to_string());
+
+    destination.extend(&text[..length]);
+
+    Some((length, destination))
+}
+
+/// Splits raw text into text and extended autolinks
+fn linkify_text(text: &[Token]) -> Vec<Result> {
+    let mut results = vec![];
+
+    let mut start = 0;
+    let mut index = 0;
+
+    while index < text.len() {
+        // Links have to start at the beginning of a word
+        let boundary = index == 0 || text[index - 1].is_whitespace() || ['*', '_', '~', '('].contains(&text[index - 1]);
+
+        let link = if boundary {
+            extended_url(&text[index..]).or_else(|| extended_email(&text[index..]))
+        } else {
+            None
+        };
+
+        match link {
+            Some((length, destination)) => {
+                if index > start {
+                    results.push(raw(text[start..index].to_vec()));
+                }
+
+                results.push(autolink_result(text[index..index + length].to_vec(), destination));
+
+                index += length;
+                start = index;
+            },
+            None => index += 1
+        }
+    }
+
+    if start < text.len() {
+        results.push(raw(text[start..].to_vec()));
+    }
+
+    results
+}
+
+/// Turns bare URLs and e-mail addresses in the raw text of the result tree into links
+pub fn linkify(results: Vec<Result>) -> Vec<Result> {
+    results.into_iter().flat_map(|mut result| {
+        if result.detector == Detector::RawDetector {
+            return linkify_text(&result.content.unwrap_or_default());
+        }
+
+        // Links, code and math are never linkified
+        let verbatim = ["a", "wikilink", "code", "pre", "math"].iter().any(|tag| result.get_property("tag") == Value::String(tag.to_string()));
+
+        if !verbatim {
+            if let Some(children) = result.children {
+                result.children = Some(linkify(children));
+            }
+        }
+
+        vec![result]
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn href(result: &Result) -> Value {
+        match result.get_property("attributes") {
+            Value::Dict(attributes) => attributes.get("href"),
+            _ => Value::NoneValue
+        }
+    }
+
+    fn text(result: &Result) -> String {
+        match &result.children {
+            Some(children) => children[0].content.clone().unwrap().to_string(),
+            None => result.content.clone().unwrap().to_string()
+        }
+    }
+
+    fn linkified(input: &str) -> Vec<(String, Value)> {
+        linkify(vec![raw(Queue::from_string(input.to_string()))]).iter().map(|result| (text(result), href(result))).collect()
+    }
+
+    #[test]
+    fn test_autolink() {
+        let mut queue = Queue::from_string("<https://example.com/a?b=c> rest".to_string());
+
+        let result = detect_autolink(&mut queue).unwrap();
+
+        assert_eq!(queue, Queue::from_string(" rest".to_string()));
+        assert_eq!(href(&result), Value::String("https://example.com/a?b=c".to_string()));
+        assert_eq!(text(&result), "https://example.com/a?b=c".to_string());
+
+        let mut queue = Queue::from_string("<foo@bar.example.com>".to_string());
+
+        assert_eq!(href(&detect_autolink(&mut queue).unwrap()), Value::String("mailto:foo@bar.example.com".to_string()));
+
+        assert!(detect_autolink(&mut Queue::from_string("<https://a b>".to_string())).is_none());
+        assert!(detect_autolink(&mut Queue::from_string("<div>".to_string())).is_none());
+        assert!(detect_autolink(&mut Queue::from_string("<m:abc>".to_string())).is_none());
+    }
+
+    #[test]
+    fn test_linkify() {
+        assert_eq!(
+            linkified("Visit www.commonmark.org/help. Now"),
+            vec![
+                ("Visit ".to_string(), Value::NoneValue),
+                ("www.commonmark.org/help".to_string(), Value::String("http://www.commonmark.org/help".to_string())),
+                (". Now".to_string(), Value::NoneValue)
+            ]
+        );
+
+        assert_eq!(
+            linkified("(https://en.wikipedia.org/wiki/Rust_(language)))"),
+            vec![
+                ("(".to_string(), Value::NoneValue),
+                ("https://en.wikipedia.org/wiki/Rust_(language)".to_string(), Value::String("https://en.wikipedia.org/wiki/Rust_(language)".to_string())),
+                ("))".to_string(), Value::NoneValue)
+            ]
+        );
+
+        assert_eq!(
+            linkified("mail foo.bar@example.com."),
+            vec![
+                ("mail ".to_string(), Value::NoneValue),
+                ("foo.bar@example.com".to_string(), Value::String("mailto:foo.bar@example.com".to_string())),
+                (".".to_string(), Value::NoneValue)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_linkify_invalid() {
+        assert_eq!(linkified("www.com_x.y_z and xwww.a.b").len(), 1);
+        assert_eq!(linkified("https://localhost").len(), 1);
+        assert_eq!(linkified("http://a.b/&amp;").last().unwrap().0, "&amp;".to_string());
+    }
+}
@@ -0,0 +1,178 @@
+// Blockquote Detector
+// ------------------------
+// Detects lines starting with `>`:
+//
+//     > Quoted text
+//     continued lazily
+//     > > Nested quote
+//
+// The `>` markers are stripped and the inner lines are parsed as blocks again,
+// so quotes can contain headings, code, lists and further quotes.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, interrupts_paragraph, node, class_attributes};
+use crate::markdown::fenced_code::is_fence;
+use crate::markdown::line::{take_line, peek_line, is_blank, indentation, expand_indentation};
+use crate::types::{Queue, Token};
+
+/// Returns the line without its `>` marker if it is part of a blockquote
+pub fn quote_content(line: &[Token]) -> Option<Queue> {
+    let indent = indentation(line);
+
+    if indent > 3 || line.get(indent) != Some(&'>') {
+        return None;
+    }
+
+    // An optional space (or one column of a tab) after the marker belongs to it
+    let mut content = expand_indentation(&line[indent + 1..], indent + 1);
+
+    if content.first() == Some(&' ') {
+        content.remove(0);
+    }
+
+    Some(content)
+}
+
+/// Whether the line starts a blockquote
+pub fn is_blockquote(line: &[Token]) -> bool {
+    quote_content(line).is_some()
+}
+
+/// Strips the markers of all nested quotes from an inner line
+fn innermost_content(line: &[Token]) -> Queue {
+    let mut content = line.to_vec();
+
+    while let Some(inner) = quote_content(&content) {
+        content = inner;
+    }
+
+    content
+}
+
+/// Collects the inner lines of a blockquote including lazy continuation lines
+pub fn take_quote_lines(queue: &mut &[Token]) -> Option<Vec<Queue>> {
+    let mut lines: Vec<Queue> = vec![];
+
+    // Whether a fenced code block is open in the (possibly nested) quote
+    let mut in_fence = false;
+
+    while !queue.is_empty() {
+        let line = peek_line(queue);
+
+        match quote_content(&line) {
+            Some(content) => {
+                let inner = innermost_content(&content);
+
+                in_fence = if in_fence { !is_fence(&inner) } else { is_fence(&inner) };
+
+                lines.push(content);
+            },
+            None => {
+                // Paragraph text inside the (possibly nested) quote can be continued without a marker
+                let last = innermost_content(&lines.last().cloned().unwrap_or_default());
+
+                let lazy = !in_fence && !is_blank(&last) && indentation(&last) < 4 && !interrupts_paragraph(&last);
+
+                if !lazy || is_blank(&line) || interrupts_paragraph(&line) {
+                    break;
+                }
+
+                lines.push(line);
+            }
+        }
+
+        take_line(queue);
+    }
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines)
+    }
+}
+
+pub fn detect_blockquote(queue: &mut &[Token]) -> Option<Result> {
+    let lines = take_quote_lines(queue)?;
+
+    let mut content: Queue = vec![];
+
+    for line in lines {
+        content.extend(line);
+        content.push('\n');
+    }
+
+    Some(node(MarkdownDetector::BlockquoteDetector, "blockquote", class_attributes(), Some(content), None))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn blockquote(input: &str) -> (Option<String>, Option<Result>) {
+        let mut queue = Queue::from_string(input.to_string());
+
+        let (_, consumed, result) = queue.consume(&Detector::MarkdownDetector(MarkdownDetector::BlockquoteDetector));
+
+        (consumed, result)
+    }
+
+    #[test]
+    fn test_blockquote_detector() {
+        let (consumed, result) = blockquote("> first\n>second\n>\tthird\n\nText");
+
+        assert_eq!(consumed.unwrap(), "> first\n>second\n>\tthird\n".to_string());
+        assert_eq!(result.unwrap().content, Some(Queue::from_string("first\nsecond\n  third\n".to_string())));
+
+        let (_, result) = blockquote("    > code");
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_blockquote_lazy_continuation() {
+        let (consumed, result) = blockquote("> quoted\nlazy\n# Heading");
+
+        assert_eq!(consumed.unwrap(), "> quoted\nlazy\n".to_string());
+        assert_eq!(result.unwrap().content, Some(Queue::from_string("quoted\nlazy\n".to_string())));
+
+        let (consumed, _) = blockquote("> # Heading\nno lazy");
+
+        assert_eq!(consumed.unwrap(), "> # Heading\n".to_string());
+
+        let (consumed, _) = blockquote("> > nested\nlazy");
+
+        assert_eq!(consumed.unwrap(), "> > nested\nlazy".to_string());
+
+        // Code of an open fence can't be continued
+        let (consumed, _) = blockquote("> ```\n> code\nlazy");
+
+        assert_eq!(consumed.unwrap(), "> ```\n> code\n".to_string());
+
+        let (consumed, _) = blockquote("> ```\n> code\n> ```\n> text\nlazy");
+
+        assert_eq!(consumed.unwrap(), "> ```\n> code\n> ```\n> text\nlazy".to_string());
+    }
+
+    #[test]
+    fn test_blockquote_nested_blocks() {
+        let mut queue = Queue::from_string("> # Title\n> > nested".to_string());
+
+        let results = queue.consume_any(
+            &[
+                Detector::MarkdownDetector(MarkdownDetector::BlockquoteDetector),
+                Detector::MarkdownDetector(MarkdownDetector::HeadingDetector)
+            ]
+        ).unwrap();
+
+        assert_eq!(results.len(), 1);
+
+        let children = results[0].children.clone().unwrap();
+
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0].get_property("tag"), Value::String("h1".to_string()));
+        assert_eq!(children[1].get_property("tag"), Value::String("blockquote".to_string()));
+    }
+}
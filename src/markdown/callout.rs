@@ -0,0 +1,256 @@
+// Callout Detector
+// ------------------------
+// Detects callouts (admonitions) in the GitHub blockquote form and the fenced container form:
+//
+//     > [!NOTE] Optional title       ::: warning Optional title
+//     > Content                      Content
+//                                    :::
+//
+// The results are `callout` nodes with the lowercase `kind` and the optional `title` as
+// attributes, the content is parsed as blocks again. Only kinds of the allow-list are callouts
+// (others stay blockquotes or text). A container is closed by a line of at least as many
+// colons as its opening fence, containers can be nested. Fences in fenced code blocks are
+// part of the code.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, node, class_attributes};
+use crate::markdown::blockquote::take_quote_lines;
+use crate::markdown::fenced_code::{detect_fenced_code, is_fence};
+use crate::markdown::line::{take_line, indentation, trim};
+use crate::types::{Queue, Token, Value};
+
+pub const MIN_FENCE_LENGTH: usize = 3;
+
+/// The allowed callout kinds (the ones of GitHub by default)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalloutKinds {
+    pub kinds: Vec<String>
+}
+
+impl Default for CalloutKinds {
+    fn default() -> Self {
+        Self::new(
+            ["note", "tip", "important", "warning", "caution"].iter().map(|kind| kind.to_string()).collect()
+        )
+    }
+}
+
+impl CalloutKinds {
+    pub fn new(kinds: Vec<String>) -> Self {
+        Self {
+            kinds: kinds.iter().map(|kind| kind.to_lowercase()).collect()
+        }
+    }
+
+    /// The lowercase kind if it is allowed
+    pub fn get(&self, kind: &[Token]) -> Option<String> {
+        let kind = kind.iter().collect::<String>().to_lowercase();
+
+        if self.kinds.contains(&kind) { Some(kind) } else { None }
+    }
+}
+
+fn callout_result(kinds: &CalloutKinds, kind: String, title: Queue, content: Queue) -> Result {
+    let mut attributes = class_attributes();
+
+    attributes.set("kind", Value::String(kind));
+
+    if !title.is_empty() {
+        attributes.set("title", Value::String(title.iter().collect()));
+    }
+
+    node(MarkdownDetector::CalloutDetector(kinds.clone()), "callout", attributes, Some(content), None)
+}
+
+fn is_kind(token: &Token) -> bool {
+    token.is_alphanumeric() || *token == '-' || *token == '_'
+}
+
+/// Parses `[!KIND] Title` at the start of the first line of a quote
+fn quote_marker(line: &[Token]) -> Option<(Queue, Queue)> {
+    if !line.starts_with(&['[', '!']) {
+        return None;
+    }
+
+    let end = line.iter().position(|token| *token == ']')?;
+
+    let kind = line[2..end].to_vec();
+
+    if kind.is_empty() || !kind.iter().all(is_kind) {
+        return None;
+    }
+
+    Some((kind, trim(&line[end + 1..])))
+}
+
+fn detect_quote_callout(queue: &mut &[Token], kinds: &CalloutKinds) -> Option<Result> {
+    let mut rest = *queue;
+
+    let lines = take_quote_lines(&mut rest)?;
+
+    let (kind, title) = quote_marker(&lines[0])?;
+
+    let kind = kinds.get(&kind)?;
+
+    let mut content: Queue = vec![];
+
+    for line in &lines[1..] {
+        content.extend(line);
+        content.push('\n');
+    }
+
+    let consumed = queue.len() - rest.len();
+
+    *queue = &queue[consumed..];
+
+    Some(callout_result(kinds, kind, title, content))
+}
+
+/// Parses a container fence, returns its length and the kind and title of an opening fence
+fn container_fence(line: &[Token]) -> Option<(usize, Queue, Queue)> {
+    let indent = indentation(line);
+
+    if indent > 3 {
+        return None;
+    }
+
+    let line = trim(line);
+
+    let length = line.iter().take_while(|token| **token == ':').count();
+
+    if length < MIN_FENCE_LENGTH {
+        return None;
+    }
+
+    let info = trim(&line[length..]);
+
+    let kind = info.iter().take_while(|token| is_kind(token)).cloned().collect::<Queue>();
+
+    // Anything else than a word has to be separated from it
+    if !info[kind.len()..].first().map_or(true, |token| token.is_whitespace()) {
+        return None;
+    }
+
+    Some((length, kind.clone(), trim(&info[kind.len()..])))
+}
+
+fn detect_container_callout(queue: &mut &[Token], kinds: &CalloutKinds) -> Option<Result> {
+    let mut rest = *queue;
+
+    let (length, kind, title) = container_fence(&take_line(&mut rest))?;
+
+    let kind = kinds.get(&kind)?;
+
+    let mut content: Queue = vec![];
+
+    // Number of nested containers that are open
+    let mut depth: usize = 0;
+
+    loop {
+        if rest.is_empty() {
+            // A container has to be closed
+            return None;
+        }
+
+        let start = rest;
+
+        let line = take_line(&mut rest);
+
+        if is_fence(&line) {
+            // The code block is taken as a whole
+            rest = start;
+
+            detect_fenced_code(&mut rest);
+
+            content.extend(&start[..start.len() - rest.len()]);
+
+            continue;
+        }
+
+        match container_fence(&line) {
+            Some((closing, kind, _)) if kind.is_empty() && depth == 0 && closing >= length => break,
+            Some((_, kind, _)) if kind.is_empty() => depth = depth.saturating_sub(1),
+            Some(_) => depth += 1,
+            None => {}
+        }
+
+        content.extend(line);
+        content.push('\n');
+    }
+
+    let consumed = queue.len() - rest.len();
+
+    *queue = &queue[consumed..];
+
+    Some(callout_result(kinds, kind, title, content))
+}
+
+pub fn detect_callout(queue: &mut &[Token], kinds: &CalloutKinds) -> Option<Result> {
+    detect_quote_callout(queue, kinds).or_else(|| detect_container_callout(queue, kinds))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn callout(input: &str, kinds: &CalloutKinds) -> Option<(Value, Value, String, String)> {
+        let text = Queue::from_string(input.to_string());
+
+        let mut queue = &text[..];
+
+        let result = detect_callout(&mut queue, kinds)?;
+
+        let attributes = match result.get_property("attributes") {
+            Value::Dict(attributes) => attributes,
+            _ => return None
+        };
+
+        Some((attributes.get("kind"), attributes.get("title"), result.content?.to_string(), queue.iter().collect()))
+    }
+
+    #[test]
+    fn test_quote_callout() {
+        let kinds = CalloutKinds::default();
+
+        assert_eq!(
+            callout("> [!Note]\n> Some *text*\n> > nested\n\nAfter", &kinds),
+            Some((Value::String("note".to_string()), Value::NoneValue, "Some *text*\n> nested\n".to_string(), "\nAfter".to_string()))
+        );
+
+        assert_eq!(
+            callout("> [!WARNING] Be careful\n> text", &kinds).map(|callout| callout.1),
+            Some(Value::String("Be careful".to_string()))
+        );
+
+        assert_eq!(callout("> [!exam]\n> text", &kinds), None);
+        assert_eq!(callout("> text\n> [!NOTE]", &kinds), None);
+    }
+
+    #[test]
+    fn test_container_callout() {
+        let kinds = CalloutKinds::new(vec!["exam".to_string(), "Note".to_string()]);
+
+        assert_eq!(
+            callout(":::exam Final exam\nText\n:::\nAfter", &kinds),
+            Some((Value::String("exam".to_string()), Value::String("Final exam".to_string()), "Text\n".to_string(), "After".to_string()))
+        );
+
+        assert_eq!(
+            callout("::::: note\n::: exam\ninner\n:::\n:::::", &kinds).map(|callout| callout.2),
+            Some("::: exam\ninner\n:::\n".to_string())
+        );
+
+        assert_eq!(
+            callout("::: note\n```\n:::\n```\n:::", &kinds).map(|callout| callout.2),
+            Some("```\n:::\n```\n".to_string())
+        );
+
+        assert_eq!(callout("::: warning\nText\n:::", &kinds), None);
+        assert_eq!(callout("::: note\nunclosed", &kinds), None);
+        assert_eq!(callout(":::note: text\n:::", &kinds), None);
+        assert_eq!(callout(":: note\n::", &kinds), None);
+    }
+}
@@ -0,0 +1,139 @@
+// Code Span Detector
+// ------------------------
+// Detects inline code between backtick runs of the same length:
+//
+//     `code` or `` code with ` inside ``
+//
+// The content is kept verbatim and never parsed again.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, node, class_attributes};
+use crate::types::Queue;
+
+fn backtick_run(queue: &[char], start: usize) -> usize {
+    queue[start..].iter().take_while(|token| **token == '`').count()
+}
+
+pub fn detect_code_span(queue: &mut Queue) -> Option<Result> {
+    let length = backtick_run(queue, 0);
+
+    if length == 0 {
+        return None;
+    }
+
+    // Find a closing run with exactly the same length
+    let mut index = length;
+    let mut end = None;
+
+    while index < queue.len() {
+        if queue[index] == '`' {
+            let run = backtick_run(queue, index);
+
+            if run == length {
+                end = Some(index);
+
+                break;
+            }
+
+            index += run;
+        } else {
+            index += 1;
+        }
+    }
+
+    let end = match end {
+        Some(end) => end,
+        None => {
+            // Without a closing run the backticks are literal text
+            let literal: Queue = queue.drain(0..length).collect();
+
+            return Some(
+                Result::new(Detector::RawDetector, Some(literal), None, None)
+            );
+        }
+    };
+
+    let mut content: Queue = queue[length..end].iter().map(|token| if *token == '\n' { ' ' } else { *token }).collect();
+
+    queue.drain(0..end + length);
+
+    // Strip one space on both sides if the content isn't only spaces
+    if content.len() >= 2 && content[0] == ' ' && content[content.len() - 1] == ' ' && content.iter().any(|token| *token != ' ') {
+        content = content[1..content.len() - 1].to_vec();
+    }
+
+    Some(
+        node(
+            MarkdownDetector::CodeSpanDetector,
+            "code",
+            class_attributes(),
+            None,
+            Some(
+                vec![
+                    Result::new(Detector::RawDetector, Some(content), None, None)
+                ]
+            )
+        )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html::HTMLDetector;
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn code(input: &str) -> (Option<String>, Option<Result>) {
+        let mut queue = Queue::from_string(input.to_string());
+
+        let (_, consumed, result) = queue.consume(&Detector::MarkdownDetector(MarkdownDetector::CodeSpanDetector));
+
+        (consumed, result)
+    }
+
+    fn text(result: &Result) -> String {
+        result.children.clone().unwrap()[0].content.clone().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_code_span_detector() {
+        let (consumed, result) = code("`code` text");
+
+        assert_eq!(consumed.unwrap(), "`code`".to_string());
+        assert_eq!(text(&result.unwrap()), "code".to_string());
+
+        let (_, result) = code("`` code with ` inside ``");
+
+        assert_eq!(text(&result.unwrap()), "code with ` inside".to_string());
+
+        let (_, result) = code("` `` `");
+
+        assert_eq!(text(&result.unwrap()), "``".to_string());
+    }
+
+    #[test]
+    fn test_code_span_unmatched() {
+        let (consumed, result) = code("``foo`");
+
+        assert_eq!(consumed.unwrap(), "``".to_string());
+        assert_eq!(result.unwrap().detector, Detector::RawDetector);
+    }
+
+    #[test]
+    fn test_code_span_content_not_parsed() {
+        let mut queue = Queue::from_string("Write `<div>test</div>` for a div".to_string());
+
+        let results = queue.consume_any(
+            &[
+                Detector::MarkdownDetector(MarkdownDetector::CodeSpanDetector),
+                Detector::HTMLDetector(HTMLDetector::DivDetector)
+            ]
+        ).unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[1].get_property("tag"), Value::String("code".to_string()));
+        assert_eq!(text(&results[1]), "<div>test</div>".to_string());
+    }
+}
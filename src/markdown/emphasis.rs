@@ -0,0 +1,388 @@
+// Emphasis Detector
+// ------------------------
+// Detects delimiter runs of `*` and `_` and resolves them into `em` and `strong` nodes
+// using the delimiter stack algorithm of CommonMark:
+//
+//     **bold *and* italic**
+//
+// The detector only produces delimiter nodes, `resolve_emphasis` matches them afterwards
+// on top of the result tree (so that other inline nodes like code spans or tags are
+// already detected and can't be broken up by emphasis). Delimiters of the inline extensions
+// (`~~`, `==`, ...) are resolved the same way, but only match runs of the same length.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, node, class_attributes};
+use crate::types::{Queue, Dict, Value, Token};
+
+pub const EMPHASIS_DELIMITERS: [char; 2] = ['*', '_'];
+
+pub fn detect_delimiter_run(queue: &mut Queue) -> Option<Result> {
+    let delimiter = *queue.first()?;
+
+    if !EMPHASIS_DELIMITERS.contains(&delimiter) {
+        return None;
+    }
+
+    let mut length = 0;
+
+    while queue.first() == Some(&delimiter) {
+        queue.remove(0);
+
+        length += 1;
+    }
+
+    let properties = Dict::from_values(
+        vec![
+            ("delimiter".to_string(), Value::String(delimiter.to_string())),
+            ("length".to_string(), Value::Integer(length))
+        ]
+    );
+
+    Some(
+        Result::new(
+            Detector::MarkdownDetector(MarkdownDetector::EmphasisDetector),
+            None,
+            Some(properties),
+            None
+        )
+    )
+}
+
+// Delimiter Resolution
+// ------------------------
+
+#[derive(Debug, Clone)]
+struct Delimiter {
+    delimiter: char,
+    length: usize,
+    original: usize,
+    can_open: bool,
+    can_close: bool,
+    element: Option<String> // Set for the delimiters of the inline extensions
+}
+
+#[derive(Debug, Clone)]
+enum Item {
+    Node(Result),
+    Delimiter(Delimiter)
+}
+
+fn is_delimiter(result: &Result) -> bool {
+    result.detector == Detector::MarkdownDetector(MarkdownDetector::EmphasisDetector) &&
+    result.get_property("tag") == Value::NoneValue
+}
+
+fn is_punctuation(token: Token) -> bool {
+    token.is_ascii_punctuation() || (!token.is_alphanumeric() && !token.is_whitespace() && !token.is_control())
+}
+
+fn raw(text: Queue) -> Result {
+    Result::new(Detector::RawDetector, Some(text), None, None)
+}
+
+/// Blocks and blank lines end the inline content emphasis can span
+fn is_barrier(result: &Result) -> bool {
+    if result.detector.is_block() {
+        return true;
+    }
+
+    if result.detector == Detector::RawDetector {
+        let text = result.content.clone().unwrap_or_default().to_string();
+
+        let lines = text.split('\n').collect::<Vec<&str>>();
+
+        return lines.len() > 2 && lines[1..lines.len() - 1].iter().any(|line| line.trim().is_empty());
+    }
+
+    false
+}
+
+/// The character next to a delimiter run as seen by the flanking rules
+fn neighbour(result: Option<&Result>, last: bool) -> Token {
+    match result {
+        None => ' ',
+        Some(result) if result.detector.is_block() => ' ',
+        // Line breaks are whitespace
+        Some(result) if matches!(
+            result.detector,
+            Detector::MarkdownDetector(MarkdownDetector::SoftBreakDetector | MarkdownDetector::LineBreakDetector)
+        ) => ' ',
+        Some(result) if result.detector == Detector::RawDetector => {
+            let content = result.content.clone().unwrap_or_default();
+
+            let token = if last { content.last() } else { content.first() };
+
+            *token.unwrap_or(&' ')
+        },
+        Some(result) if is_delimiter(result) => {
+            result.get_property("delimiter").to_str().unwrap_or_default().chars().next().unwrap_or(' ')
+        },
+        // Other inline nodes (tags, code spans, ...) start and end with punctuation
+        Some(_) => '>'
+    }
+}
+
+fn flanking(delimiter: Token, before: Token, after: Token) -> (bool, bool) {
+    let left_flanking = !after.is_whitespace() && (
+        !is_punctuation(after) || before.is_whitespace() || is_punctuation(before)
+    );
+
+    let right_flanking = !before.is_whitespace() && (
+        !is_punctuation(before) || after.is_whitespace() || is_punctuation(after)
+    );
+
+    if delimiter == '_' {
+        (
+            left_flanking && (!right_flanking || is_punctuation(before)),
+            right_flanking && (!left_flanking || is_punctuation(after))
+        )
+    } else {
+        (left_flanking, right_flanking)
+    }
+}
+
+fn emphasis_result(tag: &str, children: Vec<Result>) -> Result {
+    node(MarkdownDetector::EmphasisDetector, tag, class_attributes(), None, Some(children))
+}
+
+fn into_results(items: Vec<Item>) -> Vec<Result> {
+    let mut results: Vec<Result> = vec![];
+
+    for item in items {
+        let result = match item {
+            Item::Node(result) => result,
+            Item::Delimiter(delimiter) => {
+                if delimiter.length == 0 {
+                    continue;
+                }
+
+                raw(vec![delimiter.delimiter; delimiter.length])
+            }
+        };
+
+        // Merge adjacent raw text
+        match results.last_mut() {
+            Some(last) if last.detector == Detector::RawDetector && result.detector == Detector::RawDetector => {
+                let mut content = last.content.clone().unwrap_or_default();
+
+                content.extend(result.content.unwrap_or_default());
+
+                last.content = Some(content);
+            },
+            _ => results.push(result)
+        }
+    }
+
+    results
+}
+
+fn find_opener(items: &[Item], closer: &Delimiter, closer_index: usize) -> Option<usize> {
+    for index in (0..closer_index).rev() {
+        match &items[index] {
+            Item::Node(result) if is_barrier(result) => return None,
+            Item::Delimiter(opener) if opener.delimiter == closer.delimiter && opener.can_open && opener.length > 0 => {
+                if closer.element.is_some() {
+                    if opener.element == closer.element && opener.length == closer.length {
+                        return Some(index);
+                    }
+
+                    continue;
+                }
+
+                // Rule of three
+                if (opener.can_close || closer.can_open) &&
+                    (opener.original + closer.original) % 3 == 0 &&
+                    !(opener.original % 3 == 0 && closer.original % 3 == 0) {
+                    continue;
+                }
+
+                return Some(index);
+            },
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn process_emphasis(results: Vec<Result>) -> Vec<Result> {
+    let mut items: Vec<Item> = vec![];
+
+    for (index, result) in results.iter().enumerate() {
+        if !is_delimiter(result) {
+            items.push(Item::Node(result.clone()));
+
+            continue;
+        }
+
+        let delimiter = neighbour(Some(result), false);
+
+        let before = neighbour(if index > 0 { results.get(index - 1) } else { None }, true);
+        let after = neighbour(results.get(index + 1), false);
+
+        let (can_open, can_close) = flanking(delimiter, before, after);
+
+        let length = match result.get_property("length") {
+            Value::Integer(length) => length as usize,
+            _ => 0
+        };
+
+        let element = result.get_property("element").to_str();
+
+        items.push(
+            Item::Delimiter(
+                Delimiter { delimiter, length, original: length, can_open, can_close, element }
+            )
+        );
+    }
+
+    let mut closer_index = 0;
+
+    while closer_index < items.len() {
+        let closer = match &items[closer_index] {
+            Item::Delimiter(closer) if closer.can_close && closer.length > 0 => closer.clone(),
+            _ => {
+                closer_index += 1;
+
+                continue;
+            }
+        };
+
+        let opener_index = match find_opener(&items, &closer, closer_index) {
+            Some(opener_index) => opener_index,
+            None => {
+                closer_index += 1;
+
+                continue;
+            }
+        };
+
+        let opener_length = match &items[opener_index] {
+            Item::Delimiter(opener) => opener.length,
+            _ => 0
+        };
+
+        let (used, tag) = match &closer.element {
+            Some(element) => (closer.length, element.as_str()),
+            None if opener_length >= 2 && closer.length >= 2 => (2, "strong"),
+            None => (1, "em")
+        };
+
+        // Everything between the delimiters becomes the content of the new node
+        let inner: Vec<Item> = items.drain(opener_index + 1..closer_index).collect();
+
+        let node = emphasis_result(tag, into_results(inner));
+
+        items.insert(opener_index + 1, Item::Node(node));
+
+        closer_index = opener_index + 2;
+
+        for index in [opener_index, closer_index] {
+            if let Item::Delimiter(delimiter) = &mut items[index] {
+                delimiter.length -= used;
+            }
+        }
+    }
+
+    into_results(items)
+}
+
+/// Resolves all delimiter runs in the result tree into emphasis nodes (or plain text)
+pub fn resolve_emphasis(results: Vec<Result>) -> Vec<Result> {
+    let results = results.into_iter().map(|mut result| {
+        if let Some(children) = result.children {
+            result.children = Some(resolve_emphasis(children));
+        }
+
+        result
+    }).collect::<Vec<Result>>();
+
+    process_emphasis(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn parse(input: &str) -> Vec<Result> {
+        let mut queue = Queue::from_string(input.to_string());
+
+        let results = queue.consume_any(&[Detector::MarkdownDetector(MarkdownDetector::EmphasisDetector)]).unwrap();
+
+        resolve_emphasis(results)
+    }
+
+    fn text(result: &Result) -> String {
+        result.content.clone().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_delimiter_run() {
+        let mut queue = Queue::from_string("**bold**".to_string());
+
+        let (matched, consumed, result) = queue.consume(&Detector::MarkdownDetector(MarkdownDetector::EmphasisDetector));
+
+        assert!(matched);
+        assert_eq!(consumed.unwrap(), "**".to_string());
+        assert_eq!(result.unwrap().get_property("length"), Value::Integer(2));
+    }
+
+    #[test]
+    fn test_emphasis() {
+        let results = parse("some *emphasis* and __strong__");
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[1].get_property("tag"), Value::String("em".to_string()));
+        assert_eq!(text(&results[1].children.clone().unwrap()[0]), "emphasis".to_string());
+        assert_eq!(results[3].get_property("tag"), Value::String("strong".to_string()));
+    }
+
+    #[test]
+    fn test_emphasis_nested() {
+        let results = parse("**bold *and* italic**");
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].get_property("tag"), Value::String("strong".to_string()));
+
+        let children = results[0].children.clone().unwrap();
+
+        assert_eq!(children.len(), 3);
+        assert_eq!(text(&children[0]), "bold ".to_string());
+        assert_eq!(children[1].get_property("tag"), Value::String("em".to_string()));
+        assert_eq!(text(&children[2]), " italic".to_string());
+
+        let results = parse("***both***");
+
+        assert_eq!(results[0].get_property("tag"), Value::String("em".to_string()));
+        assert_eq!(results[0].children.clone().unwrap()[0].get_property("tag"), Value::String("strong".to_string()));
+    }
+
+    #[test]
+    fn test_emphasis_flanking() {
+        let results = parse("snake_case_name and * not emphasis *");
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(text(&results[0]), "snake_case_name and * not emphasis *".to_string());
+
+        let results = parse("intra*word*emphasis");
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[1].get_property("tag"), Value::String("em".to_string()));
+
+        let results = parse("*unclosed **strong**");
+
+        assert_eq!(text(&results[0]), "*unclosed ".to_string());
+        assert_eq!(results[1].get_property("tag"), Value::String("strong".to_string()));
+    }
+
+    #[test]
+    fn test_emphasis_blank_line() {
+        let results = parse("*first\n\nsecond*");
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(text(&results[0]), "*first\n\nsecond*".to_string());
+    }
+}
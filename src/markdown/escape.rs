@@ -0,0 +1,212 @@
+// Escape Detector
+// ------------------------
+// Detects backslash escapes of ASCII punctuation, the escaped character is never
+// interpreted as Markdown:
+//
+//     \*not emphasis\*
+//
+// The detector keeps the source form, `decode_references` resolves the escapes and the
+// entity and numeric character references (`&amp;`, `&#35;`, `&#x1F600;`) in the text
+// and the attribute values of the result tree afterwards. Code, math and autolinks are kept
+// verbatim (including the destination of autolinks), attributes of HTML tags only get
+// their character references decoded.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::MarkdownDetector;
+use crate::markdown::entities;
+use crate::types::{Queue, Token, Dict, Value};
+
+pub const MAX_DECIMAL_DIGITS: usize = 7;
+pub const MAX_HEXADECIMAL_DIGITS: usize = 6;
+pub const MAX_ENTITY_LENGTH: usize = 32;
+
+pub fn detect_escape(queue: &mut Queue) -> Option<Result> {
+    if queue.first() != Some(&'\\') || !queue.get(1)?.is_ascii_punctuation() {
+        return None;
+    }
+
+    let escape: Queue = queue.drain(0..2).collect();
+
+    Some(
+        Result::new(Detector::RawDetector, Some(escape), None, None)
+    )
+}
+
+/// Decodes the character reference at the front of the text, returns the characters and its length
+pub fn character_reference(text: &[Token]) -> Option<(String, usize)> {
+    if text.first() != Some(&'&') {
+        return None;
+    }
+
+    let end = text.iter().take(MAX_ENTITY_LENGTH + 2).position(|token| *token == ';')?;
+
+    let name = text[1..end].iter().collect::<String>();
+
+    let decoded = if let Some(number) = name.strip_prefix('#') {
+        let (digits, radix, max_digits) = match number.strip_prefix(['x', 'X']) {
+            Some(hexadecimal) => (hexadecimal, 16, MAX_HEXADECIMAL_DIGITS),
+            None => (number, 10, MAX_DECIMAL_DIGITS)
+        };
+
+        if digits.is_empty() || digits.len() > max_digits || !digits.chars().all(|token| token.is_digit(radix)) {
+            return None;
+        }
+
+        // Invalid code points and NUL are replaced
+        let code = u32::from_str_radix(digits, radix).ok()?;
+
+        match char::from_u32(code) {
+            Some(character) if code != 0 => character.to_string(),
+            _ => char::REPLACEMENT_CHARACTER.to_string()
+        }
+    } else {
+        entities::lookup(&name)?.to_string()
+    };
+
+    Some((decoded, end + 1))
+}
+
+/// Resolves character references (and backslash escapes if enabled) in the text
+pub fn decode(text: &[Token], escapes: bool) -> Queue {
+    let mut decoded: Queue = vec![];
+
+    let mut index = 0;
+
+    while index < text.len() {
+        match text[index] {
+            '\\' if escapes && text.get(index + 1).is_some_and(|token| token.is_ascii_punctuation()) => {
+                decoded.push(text[index + 1]);
+
+                index += 2;
+            },
+            '&' => match character_reference(&text[index..]) {
+                Some((characters, length)) => {
+                    decoded.extend(characters.chars());
+
+                    index += length;
+                },
+                None => {
+                    decoded.push('&');
+
+                    index += 1;
+                }
+            },
+            token => {
+                decoded.push(token);
+
+                index += 1;
+            }
+        }
+    }
+
+    decoded
+}
+
+fn decode_attributes(attributes: &Dict, escapes: bool) -> Dict {
+    let mut decoded = attributes.clone();
+
+    for (key, value) in &attributes.properties {
+        if let Value::String(value) = value {
+            decoded.set(key, Value::String(decode(&value.chars().collect::<Queue>(), escapes).iter().collect()));
+        }
+    }
+
+    decoded
+}
+
+/// Whether the children of the result are verbatim text
+fn is_verbatim(result: &Result) -> bool {
+    matches!(
+        result.detector,
+        Detector::MarkdownDetector(
+            MarkdownDetector::CodeSpanDetector |
+            MarkdownDetector::FencedCodeDetector |
+            MarkdownDetector::IndentedCodeDetector |
+            MarkdownDetector::AutolinkDetector |
+            MarkdownDetector::MathDetector |
+            MarkdownDetector::MathBlockDetector
+        )
+    )
+}
+
+/// Resolves escapes and character references in the text and attributes of the result tree
+pub fn decode_references(results: Vec<Result>) -> Vec<Result> {
+    results.into_iter().map(|mut result| {
+        if result.detector == Detector::RawDetector {
+            result.content = result.content.map(|content| decode(&content, true));
+
+            return result;
+        }
+
+        // Backslash escapes are Markdown syntax, HTML attributes only know character references
+        let escapes = matches!(result.detector, Detector::MarkdownDetector(_));
+
+        let autolink = result.detector == Detector::MarkdownDetector(MarkdownDetector::AutolinkDetector);
+
+        if let (Value::Dict(attributes), false) = (result.get_property("attributes"), autolink) {
+            result.set_property("attributes", Value::Dict(decode_attributes(&attributes, escapes)));
+        }
+
+        if !is_verbatim(&result) {
+            result.children = result.children.map(decode_references);
+        }
+
+        result
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::markdown::link::detect_link;
+    use crate::types::Queue;
+
+    use super::*;
+
+    fn decoded(input: &str) -> String {
+        decode(&Queue::from_string(input.to_string()), true).to_string()
+    }
+
+    #[test]
+    fn test_escape_detector() {
+        let mut queue = Queue::from_string("\\*text".to_string());
+
+        let result = detect_escape(&mut queue).unwrap();
+
+        assert_eq!(result.content, Some(Queue::from_string("\\*".to_string())));
+        assert_eq!(queue, Queue::from_string("text".to_string()));
+
+        assert!(detect_escape(&mut Queue::from_string("\\a".to_string())).is_none());
+        assert!(detect_escape(&mut Queue::from_string("\\\n".to_string())).is_none());
+    }
+
+    #[test]
+    fn test_character_references() {
+        assert_eq!(decoded("&amp; &copy; &ngE; &AElig;"), "& © ≧̸ Æ".to_string());
+        assert_eq!(decoded("&#35; &#1234; &#x1F600; &#X22;"), "# Ӓ 😀 \"".to_string());
+        assert_eq!(decoded("&#0; &#x110000;"), "\u{FFFD} \u{FFFD}".to_string());
+
+        assert_eq!(decoded("&nbsp &x; &#; &#87654321; &#xabcdefg;"), "&nbsp &x; &#; &#87654321; &#xabcdefg;".to_string());
+    }
+
+    #[test]
+    fn test_escapes() {
+        assert_eq!(decoded("\\*a\\* \\\\ \\a \\&amp;"), "*a* \\ \\a &amp;".to_string());
+        assert_eq!(decode(&Queue::from_string("\\*&lt;".to_string()), false).to_string(), "\\*<".to_string());
+    }
+
+    #[test]
+    fn test_decode_references() {
+        let mut queue = Queue::from_string("[a](/u\\)&amp; \"&quot;\")".to_string());
+
+        let results = decode_references(vec![detect_link(&mut queue).unwrap()]);
+
+        match results[0].get_property("attributes") {
+            Value::Dict(attributes) => {
+                assert_eq!(attributes.get("href"), Value::String("/u)&".to_string()));
+                assert_eq!(attributes.get("title"), Value::String("\"".to_string()));
+            },
+            _ => panic!("Links should have attributes")
+        }
+    }
+}
@@ -0,0 +1,162 @@
+// Fenced Code Block Detector
+// ------------------------
+// Detects code blocks between fences of at least three backticks or tildes:
+//
+//     ```python
+//     print("Hello")
+//     ```
+//
+// The info string is exposed as the `language` attribute, the content is kept verbatim.
+// A fence without a closing fence runs until the end of the document.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, node, class_attributes};
+use crate::markdown::line::{take_line, indentation, strip_indentation, trim, is_blank};
+use crate::types::{Queue, Token, Value};
+
+/// Returns (indentation, fence character, fence length, info string) of an opening fence
+fn opening_fence(line: &[Token]) -> Option<(usize, Token, usize, Queue)> {
+    let indent = indentation(line);
+
+    if indent > 3 {
+        return None;
+    }
+
+    let fence = *line.get(indent)?;
+
+    if fence != '`' && fence != '~' {
+        return None;
+    }
+
+    let length = line[indent..].iter().take_while(|token| **token == fence).count();
+
+    if length < 3 {
+        return None;
+    }
+
+    let info = trim(&line[indent + length..]);
+
+    // Backticks in the info string would make it an inline code span instead
+    if fence == '`' && info.contains(&'`') {
+        return None;
+    }
+
+    Some((indent, fence, length, info))
+}
+
+fn is_closing_fence(line: &[Token], fence: Token, length: usize) -> bool {
+    let indent = indentation(line);
+
+    if indent > 3 {
+        return false;
+    }
+
+    let closing = line[indent..].iter().take_while(|token| **token == fence).count();
+
+    closing >= length && is_blank(&line[indent + closing..])
+}
+
+/// Whether the line opens a fenced code block
+pub fn is_fence(line: &[Token]) -> bool {
+    opening_fence(line).is_some()
+}
+
+pub fn detect_fenced_code(queue: &mut &[Token]) -> Option<Result> {
+    let (indent, fence, length, info) = opening_fence(&take_line(queue))?;
+
+    let mut content: Queue = vec![];
+
+    while !queue.is_empty() {
+        let line = take_line(queue);
+
+        if is_closing_fence(&line, fence, length) {
+            break;
+        }
+
+        // Remove the indentation of the opening fence from the content
+        content.extend(strip_indentation(&line, indent));
+        content.push('\n');
+    }
+
+    let language: String = info.iter().take_while(|token| !token.is_whitespace()).collect();
+
+    let mut attributes = class_attributes();
+
+    if !language.is_empty() {
+        attributes.set("language", Value::String(language));
+    }
+
+    let mut result = node(
+        MarkdownDetector::FencedCodeDetector,
+        "pre",
+        attributes,
+        None,
+        Some(
+            vec![
+                Result::new(Detector::RawDetector, Some(content), None, None)
+            ]
+        )
+    );
+
+    result.set_property("info", Value::String(info.to_string()));
+
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn fenced(input: &str) -> Option<Result> {
+        let mut queue = Queue::from_string(input.to_string());
+
+        let (_, _, result) = queue.consume(&Detector::MarkdownDetector(MarkdownDetector::FencedCodeDetector));
+
+        result
+    }
+
+    fn code(result: &Result) -> String {
+        result.children.clone().unwrap()[0].content.clone().unwrap().to_string()
+    }
+
+    fn language(result: &Result) -> Value {
+        match result.get_property("attributes") {
+            Value::Dict(attributes) => attributes.get("language"),
+            _ => Value::NoneValue
+        }
+    }
+
+    #[test]
+    fn test_fenced_code_detector() {
+        let result = fenced("```python\nprint(\"<div>\")\n# no heading\n```\nText").unwrap();
+
+        assert_eq!(language(&result), Value::String("python".to_string()));
+        assert_eq!(code(&result), "print(\"<div>\")\n# no heading\n".to_string());
+
+        let result = fenced("~~~~\n~~~\n~~~~").unwrap();
+
+        assert_eq!(language(&result), Value::NoneValue);
+        assert_eq!(code(&result), "~~~\n".to_string());
+
+        let result = fenced("  ```\n    indented\n  ```").unwrap();
+
+        assert_eq!(code(&result), "  indented\n".to_string());
+    }
+
+    #[test]
+    fn test_fenced_code_unterminated() {
+        let result = fenced("```rust\nfn main() {}\n\nmore").unwrap();
+
+        assert_eq!(code(&result), "fn main() {}\n\nmore\n".to_string());
+    }
+
+    #[test]
+    fn test_fenced_code_incorrect() {
+        assert_eq!(fenced("``\ncode\n``"), None);
+        assert_eq!(fenced("``` a`b\ncode\n```"), None);
+        assert_eq!(fenced("    ```\ncode\n```"), None);
+    }
+}
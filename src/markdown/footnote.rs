@@ -0,0 +1,489 @@
+// Footnote Detectors
+// ------------------------
+// Detects footnote references and definitions:
+//
+//     Text with a footnote.[^note]
+//
+//     [^note]: The footnote text,
+//         continued by indented blocks.
+//
+// Definitions are collected from the block structure (like link reference definitions),
+// `resolve_footnotes` numbers the references in order of their first occurrence and
+// appends the referenced definitions as a `footnotes` section to the end of the document.
+// Undefined references are kept as text, both they and unreferenced definitions are
+// reported as diagnostics.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, interrupts_paragraph, diagnostic, node, class_attributes};
+use crate::markdown::line::{take_line, peek_line, is_blank, indentation, strip_indentation};
+use crate::markdown::reference::normalize_label;
+use crate::types::{Queue, Token, Dict, Value};
+
+pub const FOOTNOTE_INDENTATION: usize = 4;
+
+#[derive(Debug, Clone, Default)]
+pub struct Footnotes {
+    pub definitions: Vec<(String, Result)> // Normalized label and definition in document order
+}
+
+impl Footnotes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The first definition of a label wins
+    pub fn define(&mut self, label: &str, definition: Result) -> bool {
+        let label = normalize_label(&label.chars().collect::<Queue>());
+
+        if self.definitions.iter().any(|(defined, _)| *defined == label) {
+            return false;
+        }
+
+        self.definitions.push((label, definition));
+
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+}
+
+/// Parses `[^label]` at `start`, returns the label and the index after it
+fn footnote_label(queue: &[Token], start: usize) -> Option<(String, usize)> {
+    if queue.get(start) != Some(&'[') || queue.get(start + 1) != Some(&'^') {
+        return None;
+    }
+
+    let length = queue[start + 2..].iter().take_while(|token| **token != ']' && !token.is_whitespace() && **token != '[').count();
+
+    if length == 0 || queue.get(start + 2 + length) != Some(&']') {
+        return None;
+    }
+
+    Some((queue[start + 2..start + 2 + length].iter().collect(), start + 3 + length))
+}
+
+fn attributes(class: &str, pairs: Vec<(&str, String)>) -> Dict {
+    let mut attributes = class_attributes();
+
+    if !class.is_empty() {
+        attributes.set("class", Value::Array(vec![Value::String(class.to_string())]));
+    }
+
+    for (key, value) in pairs {
+        attributes.set(key, Value::String(value));
+    }
+
+    attributes
+}
+
+fn raw(text: &str) -> Result {
+    Result::new(Detector::RawDetector, Some(text.chars().collect()), None, None)
+}
+
+// Definitions
+// ------------------------
+
+pub fn detect_footnote_definition(queue: &mut &[Token]) -> Option<Result> {
+    let first = peek_line(queue);
+
+    let indent = indentation(&first);
+
+    if indent > 3 {
+        return None;
+    }
+
+    let (label, after) = footnote_label(&first, indent)?;
+
+    if first.get(after) != Some(&':') {
+        return None;
+    }
+
+    let mut lines: Vec<Queue> = vec![
+        first[after + 1..].iter().skip_while(|token| **token == ' ' || **token == '\t').cloned().collect()
+    ];
+
+    take_line(queue);
+
+    let mut rest = *queue;
+
+    // Trailing blank lines are not part of the definition
+    let mut consumed = 0;
+    let mut blank_lines = 0;
+
+    while !rest.is_empty() {
+        let line = peek_line(rest);
+
+        if is_blank(&line) {
+            blank_lines += 1;
+        } else if indentation(&line) >= FOOTNOTE_INDENTATION {
+            lines.extend(vec![vec![]; blank_lines]);
+            lines.push(strip_indentation(&line, FOOTNOTE_INDENTATION));
+
+            blank_lines = 0;
+        } else {
+            // Lazy continuation of paragraph text
+            let last = lines.last().cloned().unwrap_or_default();
+
+            if blank_lines > 0 || is_blank(&last) || interrupts_paragraph(&line) || footnote_label(&line, indentation(&line)).is_some() {
+                break;
+            }
+
+            lines.push(line);
+        }
+
+        take_line(&mut rest);
+
+        if blank_lines == 0 {
+            consumed = queue.len() - rest.len();
+        }
+    }
+
+    *queue = &queue[consumed..];
+
+    let mut content: Queue = vec![];
+
+    for line in lines {
+        content.extend(line);
+        content.push('\n');
+    }
+
+    let mut result = node(MarkdownDetector::FootnoteDefinitionDetector, "li", attributes("", vec![]), Some(content), None);
+
+    result.set_property("label", Value::String(label));
+
+    Some(result)
+}
+
+/// Removes all footnote definitions from the result tree and collects them
+pub fn collect_footnotes(results: &mut Vec<Result>, footnotes: &mut Footnotes, diagnostics: &mut Vec<Value>) {
+    collect_footnotes_from(results, 0, footnotes, diagnostics);
+}
+
+fn collect_footnotes_from(results: &mut Vec<Result>, offset: i64, footnotes: &mut Footnotes, diagnostics: &mut Vec<Value>) {
+    let mut index = 0;
+
+    while index < results.len() {
+        // Lines are relative to the content of the parent
+        let line = offset + match results[index].get_property("line") {
+            Value::Integer(line) => line,
+            _ => 0
+        };
+
+        if results[index].detector == Detector::MarkdownDetector(MarkdownDetector::FootnoteDefinitionDetector) {
+            let mut definition = results.remove(index);
+
+            // The definition is moved out of its parent, so its line is made absolute
+            definition.set_property("line", Value::Integer(line));
+
+            let label = definition.get_property("label").to_str().unwrap_or_default();
+
+            if !footnotes.define(&label, definition) {
+                diagnostics.push(diagnostic("duplicate-footnote", &label, format!("Footnote [^{}] is defined more than once", label)));
+            }
+
+            continue;
+        }
+
+        if let Some(children) = &mut results[index].children {
+            collect_footnotes_from(children, line, footnotes, diagnostics);
+        }
+
+        index += 1;
+    }
+}
+
+// References
+// ------------------------
+
+pub fn detect_footnote_reference(queue: &mut Queue) -> Option<Result> {
+    let (label, after) = footnote_label(queue, 0)?;
+
+    // `[^label]:` at the start of a line is a definition, not a reference
+    if queue.get(after) == Some(&':') {
+        return None;
+    }
+
+    queue.drain(0..after);
+
+    let mut result = node(MarkdownDetector::FootnoteReferenceDetector, "sup", attributes("footnote-ref", vec![]), None, None);
+
+    result.set_property("label", Value::String(label));
+
+    Some(result)
+}
+
+struct Numbering {
+    footnotes: Footnotes,
+    order: Vec<usize>, // Indices of the definitions in order of their first reference
+    references: Vec<usize>, // Number of references per definition
+    diagnostics: Vec<Value>
+}
+
+impl Numbering {
+    fn number(&mut self, results: Vec<Result>) -> Vec<Result> {
+        results.into_iter().map(|mut result| {
+            if result.detector != Detector::MarkdownDetector(MarkdownDetector::FootnoteReferenceDetector) {
+                result.children = result.children.map(|children| self.number(children));
+
+                return result;
+            }
+
+            let label = result.get_property("label").to_str().unwrap_or_default();
+
+            let normalized = normalize_label(&label.chars().collect::<Queue>());
+
+            let index = match self.footnotes.definitions.iter().position(|(defined, _)| *defined == normalized) {
+                Some(index) => index,
+                None => {
+                    self.diagnostics.push(diagnostic("undefined-footnote", &label, format!("Footnote [^{}] is not defined", label)));
+
+                    return raw(&format!("[^{}]", label));
+                }
+            };
+
+            if !self.order.contains(&index) {
+                self.order.push(index);
+            }
+
+            self.references[index] += 1;
+
+            let number = self.order.iter().position(|ordered| *ordered == index).unwrap() + 1;
+
+            let link = node(
+                MarkdownDetector::FootnoteReferenceDetector,
+                "a",
+                attributes("", vec![("href", format!("#fn-{}", number))]),
+                None,
+                Some(vec![raw(&number.to_string())])
+            );
+
+            let mut attributes = attributes("footnote-ref", vec![("id", reference_id(number, self.references[index]))]);
+
+            attributes.set("number", Value::Integer(number as i64));
+
+            result.set_property("attributes", Value::Dict(attributes));
+            result.children = Some(vec![link]);
+
+            result
+        }).collect()
+    }
+}
+
+fn reference_id(number: usize, occurrence: usize) -> String {
+    if occurrence == 1 {
+        format!("fnref-{}", number)
+    } else {
+        format!("fnref-{}-{}", number, occurrence)
+    }
+}
+
+/// Numbers the footnote references and appends the referenced definitions as `footnotes` section
+pub fn resolve_footnotes(results: Vec<Result>, footnotes: Footnotes, diagnostics: &mut Vec<Value>) -> Vec<Result> {
+    let count = footnotes.definitions.len();
+
+    let mut numbering = Numbering {
+        footnotes,
+        order: vec![],
+        references: vec![0; count],
+        diagnostics: vec![]
+    };
+
+    let mut results = numbering.number(results);
+
+    // Definitions can reference further footnotes
+    let mut items = vec![];
+    let mut position = 0;
+
+    while position < numbering.order.len() {
+        let index = numbering.order[position];
+
+        let definition = numbering.footnotes.definitions[index].1.clone();
+
+        let line = definition.get_property("line");
+
+        let children = numbering.number(definition.children.unwrap_or_default());
+
+        items.push((index, children, line));
+
+        position += 1;
+    }
+
+    let items = items.into_iter().enumerate().map(|(position, (index, mut children, line))| {
+        let number = position + 1;
+
+        // Links back to every reference
+        let mut backreferences = vec![];
+
+        for occurrence in 1..=numbering.references[index] {
+            backreferences.push(raw(" "));
+            backreferences.push(
+                node(
+                    MarkdownDetector::FootnoteDefinitionDetector,
+                    "a",
+                    attributes("footnote-backref", vec![("href", format!("#{}", reference_id(number, occurrence)))]),
+                    None,
+                    Some(vec![raw("↩")])
+                )
+            );
+        }
+
+        // The back references are part of the last paragraph if the footnote ends with one
+        match children.last_mut() {
+            Some(last) if last.get_property("tag") == Value::String("p".to_string()) => {
+                last.children.get_or_insert_with(Vec::new).extend(backreferences);
+            },
+            _ => children.extend(backreferences)
+        }
+
+        let mut item = node(
+            MarkdownDetector::FootnoteDefinitionDetector,
+            "li",
+            attributes("", vec![("id", format!("fn-{}", number))]),
+            None,
+            Some(children)
+        );
+
+        // The line of the definition (the item is not in the source order)
+        item.set_property("line", line);
+
+        item
+    }).collect::<Vec<Result>>();
+
+    for (index, (_, definition)) in numbering.footnotes.definitions.iter().enumerate() {
+        if numbering.references[index] == 0 {
+            let label = definition.get_property("label").to_str().unwrap_or_default();
+
+            numbering.diagnostics.push(diagnostic("unreferenced-footnote", &label, format!("Footnote [^{}] is never referenced", label)));
+        }
+    }
+
+    diagnostics.extend(numbering.diagnostics);
+
+    if !items.is_empty() {
+        let list = node(MarkdownDetector::FootnoteDefinitionDetector, "ol", attributes("", vec![]), None, Some(items));
+
+        results.push(
+            node(MarkdownDetector::FootnoteDefinitionDetector, "section", attributes("footnotes", vec![]), None, Some(vec![list]))
+        );
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn detectors() -> Vec<Detector> {
+        vec![
+            Detector::MarkdownDetector(MarkdownDetector::FootnoteDefinitionDetector),
+            Detector::MarkdownDetector(MarkdownDetector::ParagraphDetector),
+            Detector::MarkdownDetector(MarkdownDetector::FootnoteReferenceDetector)
+        ]
+    }
+
+    fn resolve(input: &str) -> (Vec<Result>, Vec<Value>) {
+        let mut queue = Queue::from_string(input.to_string());
+
+        let mut blocks = queue.consume_blocks(&detectors()).unwrap();
+
+        let mut footnotes = Footnotes::new();
+        let mut diagnostics = vec![];
+
+        collect_footnotes(&mut blocks, &mut footnotes, &mut diagnostics);
+
+        for (_, definition) in footnotes.definitions.iter_mut() {
+            definition.children = definition.children.take().map(|children| parse_inlines(children, &detectors()));
+        }
+
+        let results = resolve_footnotes(parse_inlines(blocks, &detectors()), footnotes, &mut diagnostics);
+
+        (results, diagnostics)
+    }
+
+    fn kinds(diagnostics: &[Value]) -> Vec<Value> {
+        diagnostics.iter().map(|diagnostic| match diagnostic {
+            Value::Dict(diagnostic) => diagnostic.get("kind"),
+            _ => Value::NoneValue
+        }).collect()
+    }
+
+    #[test]
+    fn test_footnote_definition() {
+        let text = Queue::from_string("[^a]: First\n    indented\nlazy\n\n    second paragraph\n\nText".to_string());
+
+        let mut queue = &text[..];
+
+        let result = detect_footnote_definition(&mut queue).unwrap();
+
+        assert_eq!(result.get_property("label"), Value::String("a".to_string()));
+        assert_eq!(result.content, Some(Queue::from_string("First\nindented\nlazy\n\nsecond paragraph\n".to_string())));
+        assert_eq!(queue.to_vec(), Queue::from_string("\nText".to_string()));
+
+        assert!(detect_footnote_definition(&mut &Queue::from_string("[^a b]: no".to_string())[..]).is_none());
+        assert!(detect_footnote_definition(&mut &Queue::from_string("[^]: no".to_string())[..]).is_none());
+    }
+
+    #[test]
+    fn test_footnote_numbering() {
+        let (results, diagnostics) = resolve("A[^second] b[^First] c[^second].\n\n[^first]: One\n[^second]: Two[^third]\n[^third]: Three\n");
+
+        assert!(diagnostics.is_empty());
+
+        let references = results[0].children.clone().unwrap();
+
+        let numbers = references.iter()
+            .filter(|result| result.get_property("tag") == Value::String("sup".to_string()))
+            .map(|result| match result.get_property("attributes") {
+                Value::Dict(attributes) => (attributes.get("number"), attributes.get("id")),
+                _ => (Value::NoneValue, Value::NoneValue)
+            })
+            .collect::<Vec<(Value, Value)>>();
+
+        assert_eq!(
+            numbers,
+            vec![
+                (Value::Integer(1), Value::String("fnref-1".to_string())),
+                (Value::Integer(2), Value::String("fnref-2".to_string())),
+                (Value::Integer(1), Value::String("fnref-1-2".to_string()))
+            ]
+        );
+
+        let section = results.last().unwrap();
+
+        assert_eq!(section.get_property("tag"), Value::String("section".to_string()));
+
+        let items = section.children.clone().unwrap()[0].children.clone().unwrap();
+
+        assert_eq!(items.len(), 3);
+
+        // Every back reference has the same glyph
+        let backreferences = items[0].children.clone().unwrap()[0].children.clone().unwrap().into_iter()
+            .filter(|result| result.get_property("tag") == Value::String("a".to_string()))
+            .map(|result| result.children.unwrap()[0].content.clone().unwrap().to_string())
+            .collect::<Vec<String>>();
+
+        assert_eq!(backreferences, vec!["↩".to_string(), "↩".to_string()]);
+
+        // The items keep the line of their definition
+        let lines = items.iter().map(|item| item.get_property("line")).collect::<Vec<Value>>();
+
+        assert_eq!(lines, vec![Value::Integer(3), Value::Integer(2), Value::Integer(4)]);
+    }
+
+    #[test]
+    fn test_footnote_diagnostics() {
+        let (results, diagnostics) = resolve("A[^missing].\n\n[^unused]: Never\n");
+
+        assert_eq!(kinds(&diagnostics), vec![Value::String("undefined-footnote".to_string()), Value::String("unreferenced-footnote".to_string())]);
+
+        // Undefined references stay text and there is no footnotes section
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].children.clone().unwrap()[1].content, Some(Queue::from_string("[^missing]".to_string())));
+    }
+}
@@ -0,0 +1,222 @@
+// Front Matter
+// ------------------------
+// Extracts a YAML or TOML front matter block at the very start of the document:
+//
+//     ---                    +++
+//     title: Page            title = "Page"
+//     tags: [a, b]           tags = ["a", "b"]
+//     ---                    +++
+//
+// A YAML block may also be closed by `...`. The block has to be a mapping, it is returned
+// as a dict separate from the content. The lines of the block are blanked out in the input,
+// so that the `line` of the blocks after it stays the line in the source. An invalid block
+// is left in the content (it may be a thematic break and a setext heading).
+// ------------------------
+
+use toml::Value as Toml;
+use yaml_rust2::{Yaml, YamlLoader};
+
+use crate::markdown::diagnostic;
+use crate::types::{Dict, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Yaml,
+    Toml
+}
+
+impl Format {
+    fn name(&self) -> &'static str {
+        match self {
+            Format::Yaml => "yaml",
+            Format::Toml => "toml"
+        }
+    }
+
+    fn is_closing(&self, line: &str) -> bool {
+        match self {
+            Format::Yaml => line == "---" || line == "...",
+            Format::Toml => line == "+++"
+        }
+    }
+}
+
+/// Splits the front matter from the input, returns its format, its source and the number of lines it takes
+pub fn split_front_matter(input: &str) -> Option<(Format, String, usize)> {
+    let mut lines = input.split('\n').map(|line| line.trim_end());
+
+    let format = match lines.next()? {
+        "---" => Format::Yaml,
+        "+++" => Format::Toml,
+        _ => return None
+    };
+
+    let mut source: Vec<&str> = vec![];
+
+    for line in lines {
+        if format.is_closing(line) {
+            return Some((format, source.join("\n"), source.len() + 2));
+        }
+
+        source.push(line);
+    }
+
+    None
+}
+
+fn from_yaml(yaml: Yaml) -> Value {
+    match yaml {
+        Yaml::String(string) => Value::String(string),
+        Yaml::Integer(number) => Value::Integer(number),
+        Yaml::Real(number) => number.parse::<f64>().map_or(Value::String(number), Value::Float),
+        Yaml::Boolean(boolean) => Value::Boolean(boolean),
+        // Keys with a null value are kept
+        Yaml::Null => Value::Null,
+        Yaml::Array(array) => Value::Array(array.into_iter().map(from_yaml).collect()),
+        Yaml::Hash(hash) => Value::Dict(
+            Dict::from_values(
+                hash.into_iter().filter_map(|(key, value)| {
+                    let key = match key {
+                        Yaml::String(key) | Yaml::Real(key) => key,
+                        Yaml::Integer(key) => key.to_string(),
+                        Yaml::Boolean(key) => key.to_string(),
+                        _ => return None
+                    };
+
+                    Some((key, from_yaml(value)))
+                }).collect()
+            )
+        ),
+        _ => Value::NoneValue
+    }
+}
+
+fn from_toml(toml: Toml) -> Value {
+    match toml {
+        Toml::String(string) => Value::String(string),
+        Toml::Integer(number) => Value::Integer(number),
+        Toml::Float(number) => Value::Float(number),
+        Toml::Boolean(boolean) => Value::Boolean(boolean),
+        Toml::Datetime(datetime) => Value::String(datetime.to_string()),
+        Toml::Array(array) => Value::Array(array.into_iter().map(from_toml).collect()),
+        Toml::Table(table) => Value::Dict(
+            Dict::from_values(table.into_iter().map(|(key, value)| (key, from_toml(value))).collect())
+        )
+    }
+}
+
+/// Parses the source of a front matter block into a dict
+fn parse_front_matter(format: Format, source: &str) -> std::result::Result<Dict, String> {
+    let value = match format {
+        Format::Yaml => {
+            let documents = YamlLoader::load_from_str(source).map_err(|error| error.to_string())?;
+
+            match documents.into_iter().next() {
+                Some(yaml) => from_yaml(yaml),
+                // An empty block
+                None => Value::Dict(Dict::new())
+            }
+        },
+        Format::Toml => from_toml(source.parse::<Toml>().map_err(|error| error.to_string())?)
+    };
+
+    match value {
+        Value::Dict(dict) => Ok(dict),
+        _ => Err("The front matter is not a mapping".to_string())
+    }
+}
+
+/// Extracts the front matter, returns its dict (empty without valid front matter) and the input without it
+/// (the unchanged input if the front matter is invalid)
+pub fn extract_front_matter(input: &str, diagnostics: &mut Vec<Value>) -> (Dict, String) {
+    let (format, source, lines) = match split_front_matter(input) {
+        Some(front_matter) => front_matter,
+        None => return (Dict::new(), input.to_string())
+    };
+
+    match parse_front_matter(format, &source) {
+        Ok(meta) => {
+            let rest = input.split('\n').skip(lines).collect::<Vec<&str>>().join("\n");
+
+            (meta, "\n".repeat(lines) + &rest)
+        },
+        Err(error) => {
+            diagnostics.push(
+                diagnostic("invalid-front-matter", format.name(), format!("The {} front matter is invalid: {}", format.name(), error))
+            );
+
+            (Dict::new(), input.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::Value;
+
+    use super::*;
+
+    #[test]
+    fn test_split_front_matter() {
+        let (format, source, lines) = split_front_matter("---\ntitle: Page\n...\n# Text").unwrap();
+
+        assert_eq!(format, Format::Yaml);
+        assert_eq!(source, "title: Page".to_string());
+        assert_eq!(lines, 3);
+
+        assert!(split_front_matter("---\ntitle: Page\n").is_none());
+        assert!(split_front_matter("+++\ntitle = 1\n---\n").is_none());
+        assert!(split_front_matter("\n---\ntitle: Page\n---\n").is_none());
+    }
+
+    #[test]
+    fn test_yaml_front_matter() {
+        let mut diagnostics = vec![];
+
+        let (meta, content) = extract_front_matter("---\ntitle: Page\nauthors: [a, b]\nversion: 1.5\n---\nText", &mut diagnostics);
+
+        assert_eq!(meta.get("title"), Value::String("Page".to_string()));
+        assert_eq!(meta.get("authors"), Value::Array(vec![Value::String("a".to_string()), Value::String("b".to_string())]));
+        assert_eq!(meta.get("version"), Value::Float(1.5));
+        assert_eq!(content, "\n\n\n\n\nText".to_string());
+        assert!(diagnostics.is_empty());
+
+        let (meta, _) = extract_front_matter("---\nsummary: null\nimage: ~\n---\n", &mut diagnostics);
+
+        assert_eq!(meta.get("summary"), Value::Null);
+        assert_eq!(meta.get("image"), Value::Null);
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_toml_front_matter() {
+        let mut diagnostics = vec![];
+
+        let (meta, content) = extract_front_matter("+++\ntitle = \"Page\"\n[page]\ndraft = true\n+++\nText", &mut diagnostics);
+
+        assert_eq!(meta.get("title"), Value::String("Page".to_string()));
+        assert_eq!(meta.get("page"), Value::Dict(Dict::from_values(vec![("draft".to_string(), Value::Boolean(true))])));
+        assert_eq!(content, "\n\n\n\n\nText".to_string());
+    }
+
+    #[test]
+    fn test_invalid_front_matter() {
+        let mut diagnostics = vec![];
+
+        let (meta, content) = extract_front_matter("---\n- a list\n---\nText", &mut diagnostics);
+
+        assert!(meta.is_empty());
+        assert_eq!(content, "---\n- a list\n---\nText".to_string());
+        assert_eq!(diagnostics.len(), 1);
+
+        let input = "---\n\nIntro paragraph here.\n\n---\n\n# Next";
+
+        assert_eq!(extract_front_matter(input, &mut diagnostics).1, input.to_string());
+        assert_eq!(diagnostics.len(), 2);
+
+        let (meta, _) = extract_front_matter("+++\ntitle = \n+++\n", &mut diagnostics);
+
+        assert!(meta.is_empty());
+        assert_eq!(diagnostics.len(), 3);
+    }
+}
@@ -0,0 +1,126 @@
+// ATX Heading Detector
+// ------------------------
+// Detects `#` to `######` headings at the start of a line:
+//
+//     ## Heading ##
+//
+// The optional closing sequence of `#` is trimmed, the remaining text is the content of the heading.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, node, class_attributes};
+use crate::markdown::line::{take_line, indentation, trim};
+use crate::types::{Queue, Token, Value};
+
+pub fn detect_atx_heading(queue: &mut &[Token]) -> Option<Result> {
+    let line = take_line(queue);
+
+    // Up to three spaces of indentation are allowed
+    let indent = indentation(&line);
+
+    if indent > 3 {
+        return None;
+    }
+
+    let level = line[indent..].iter().take_while(|token| **token == '#').count();
+
+    if level == 0 || level > 6 {
+        return None;
+    }
+
+    let rest = line[indent + level..].to_vec();
+
+    // The opening sequence has to be followed by whitespace or the end of the line
+    if !rest.is_empty() && rest[0] != ' ' && rest[0] != '\t' {
+        return None;
+    }
+
+    let mut content = trim(&rest);
+
+    // Remove the optional closing sequence (has to be preceded by whitespace)
+    let closing = content.iter().rev().take_while(|token| **token == '#').count();
+
+    if closing == content.len() {
+        content = vec![];
+    } else if closing > 0 {
+        let before = content[content.len() - closing - 1];
+
+        if before == ' ' || before == '\t' {
+            content = trim(&content[..content.len() - closing]);
+        }
+    }
+
+    Some(
+        heading_result(MarkdownDetector::HeadingDetector, level, content)
+    )
+}
+
+/// Builds a heading result with the same shape as the HTML heading detector
+pub fn heading_result(detector: MarkdownDetector, level: usize, content: Queue) -> Result {
+    let tag = format!("h{}", level);
+
+    let mut result = node(detector, &tag, class_attributes(), Some(content), None);
+
+    result.set_property("level", Value::Integer(level as i64));
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn heading(input: &str) -> Option<Result> {
+        let mut queue = Queue::from_string(input.to_string());
+
+        let (_, _, result) = queue.consume(&Detector::MarkdownDetector(MarkdownDetector::HeadingDetector));
+
+        result
+    }
+
+    #[test]
+    fn test_heading_detector() {
+        let result = heading("## Heading\nText").unwrap();
+
+        assert_eq!(result.get_property("tag"), Value::String("h2".to_string()));
+        assert_eq!(result.get_property("level"), Value::Integer(2));
+        assert_eq!(result.content, Some(Queue::from_string("Heading".to_string())));
+
+        let result = heading("   ###### Six").unwrap();
+
+        assert_eq!(result.get_property("tag"), Value::String("h6".to_string()));
+
+        assert_eq!(heading("####### Seven"), None);
+        assert_eq!(heading("#NoSpace"), None);
+        assert_eq!(heading("    # Indented"), None);
+    }
+
+    #[test]
+    fn test_heading_detector_closing_sequence() {
+        let result = heading("# Title ###").unwrap();
+
+        assert_eq!(result.content, Some(Queue::from_string("Title".to_string())));
+
+        let result = heading("# Title#").unwrap();
+
+        assert_eq!(result.content, Some(Queue::from_string("Title#".to_string())));
+
+        let result = heading("### ###").unwrap();
+
+        assert_eq!(result.content, Some(vec![]));
+    }
+
+    #[test]
+    fn test_heading_only_at_line_start() {
+        let mut queue = Queue::from_string("Text # no heading\n# Heading".to_string());
+
+        let results = queue.consume_any(&[Detector::MarkdownDetector(MarkdownDetector::HeadingDetector)]).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].detector, Detector::RawDetector);
+        assert_eq!(results[0].content, Some(Queue::from_string("Text # no heading\n".to_string())));
+        assert_eq!(results[1].get_property("tag"), Value::String("h1".to_string()));
+    }
+}
@@ -0,0 +1,229 @@
+// Include Detector
+// ------------------------
+// Detects include directives on a line of their own:
+//
+//     {{include:Page Name}}
+//
+// The results are `include` nodes with the `page` as their attribute. `resolve_includes`
+// optionally replaces them with the blocks of the included pages: a loader returns the source
+// of a page, which is parsed again (so it can include further pages). An include of a page
+// that is already being included is a cycle, both cycles and too deeply nested includes are
+// errors with the chain of included pages. Include directives inside a line stay text (they
+// are not templates either).
+// ------------------------
+
+use std::fmt;
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, node, class_attributes};
+use crate::markdown::line::{take_line, indentation, trim};
+use crate::types::{Token, Value};
+
+pub const DEFAULT_MAX_DEPTH: usize = 8;
+
+pub const INCLUDE_PREFIX: &str = "include:";
+
+/// Returns the source of a page
+pub trait PageLoader<E> {
+    fn load(&mut self, page: &str) -> std::result::Result<String, E>;
+}
+
+impl<E, F: FnMut(&str) -> std::result::Result<String, E>> PageLoader<E> for F {
+    fn load(&mut self, page: &str) -> std::result::Result<String, E> {
+        self(page)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IncludeError<E> {
+    /// The chain of pages ending with the page included again
+    Cycle(Vec<String>),
+    /// The chain of pages exceeding the maximum depth
+    TooDeep(Vec<String>),
+    /// The loader failed
+    Loader(E)
+}
+
+impl<E: fmt::Display> fmt::Display for IncludeError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IncludeError::Cycle(chain) => write!(f, "Include cycle: {}", chain.join(" -> ")),
+            IncludeError::TooDeep(chain) => write!(f, "Includes are nested too deeply: {}", chain.join(" -> ")),
+            IncludeError::Loader(error) => error.fmt(f)
+        }
+    }
+}
+
+pub fn detect_include(queue: &mut &[Token]) -> Option<Result> {
+    let mut rest = *queue;
+
+    let line = take_line(&mut rest);
+
+    if indentation(&line) > 3 {
+        return None;
+    }
+
+    let line = trim(&line).iter().collect::<String>();
+
+    let page = line.strip_prefix("{{")?.strip_prefix(INCLUDE_PREFIX)?.strip_suffix("}}")?.trim();
+
+    if page.is_empty() || page.contains(['{', '}']) {
+        return None;
+    }
+
+    let consumed = queue.len() - rest.len();
+
+    *queue = &queue[consumed..];
+
+    let mut attributes = class_attributes();
+
+    attributes.set("page", Value::String(page.to_string()));
+
+    Some(node(MarkdownDetector::IncludeDetector, "include", attributes, None, None))
+}
+
+fn page(result: &Result) -> Option<String> {
+    if result.detector != Detector::MarkdownDetector(MarkdownDetector::IncludeDetector) {
+        return None;
+    }
+
+    match result.get_property("attributes") {
+        Value::Dict(attributes) => attributes.get("page").to_str(),
+        _ => None
+    }
+}
+
+fn splice<E>(
+    results: Vec<Result>,
+    loader: &mut dyn PageLoader<E>,
+    parse: &dyn Fn(&str) -> Vec<Result>,
+    max_depth: usize,
+    chain: &mut Vec<String>
+) -> std::result::Result<Vec<Result>, IncludeError<E>> {
+    let mut spliced: Vec<Result> = vec![];
+
+    for mut result in results {
+        if let Some(page) = page(&result) {
+            let cycle = chain.contains(&page);
+            let too_deep = chain.len() >= max_depth;
+
+            chain.push(page.clone());
+
+            if cycle {
+                return Err(IncludeError::Cycle(chain.clone()));
+            }
+
+            if too_deep {
+                return Err(IncludeError::TooDeep(chain.clone()));
+            }
+
+            let source = loader.load(&page).map_err(IncludeError::Loader)?;
+
+            spliced.extend(splice(parse(&source), loader, parse, max_depth, chain)?);
+
+            chain.pop();
+
+            continue;
+        }
+
+        if let Some(children) = result.children {
+            result.children = Some(splice(children, loader, parse, max_depth, chain)?);
+        }
+
+        spliced.push(result);
+    }
+
+    Ok(spliced)
+}
+
+/// Replaces the include nodes in the block tree with the blocks of the included pages (as returned by `parse`)
+pub fn resolve_includes<E>(
+    results: Vec<Result>,
+    loader: &mut dyn PageLoader<E>,
+    parse: &dyn Fn(&str) -> Vec<Result>,
+    max_depth: usize
+) -> std::result::Result<Vec<Result>, IncludeError<E>> {
+    splice(results, loader, parse, max_depth, &mut vec![])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn detectors() -> Vec<Detector> {
+        vec![
+            Detector::MarkdownDetector(MarkdownDetector::IncludeDetector),
+            Detector::MarkdownDetector(MarkdownDetector::HeadingDetector),
+            Detector::MarkdownDetector(MarkdownDetector::ParagraphDetector)
+        ]
+    }
+
+    fn parse(source: &str) -> Vec<Result> {
+        Queue::from_string(source.to_string()).consume_blocks(&detectors()).unwrap_or_default()
+    }
+
+    fn load(page: &str) -> std::result::Result<String, String> {
+        match page {
+            "A" => Ok("# A\n{{include:B}}".to_string()),
+            "B" => Ok("Text of B".to_string()),
+            "Loop" => Ok("{{include:Other}}".to_string()),
+            "Other" => Ok("{{include:Loop}}".to_string()),
+            _ => Err(format!("Page {} not found", page))
+        }
+    }
+
+    fn resolve(source: &str, max_depth: usize) -> std::result::Result<Vec<Result>, IncludeError<String>> {
+        resolve_includes(parse(source), &mut load, &parse, max_depth)
+    }
+
+    #[test]
+    fn test_include() {
+        let text = Queue::from_string("  {{include: Page Name }}  \nText".to_string());
+
+        let mut queue = &text[..];
+
+        let result = detect_include(&mut queue).unwrap();
+
+        assert_eq!(queue.to_vec(), Queue::from_string("Text".to_string()));
+
+        match result.get_property("attributes") {
+            Value::Dict(attributes) => assert_eq!(attributes.get("page"), Value::String("Page Name".to_string())),
+            _ => panic!("Includes should have attributes")
+        }
+
+        assert!(detect_include(&mut &Queue::from_string("{{include:}}".to_string())[..]).is_none());
+        assert!(detect_include(&mut &Queue::from_string("{{include:A}} text".to_string())[..]).is_none());
+        assert!(detect_include(&mut &Queue::from_string("{{Template}}".to_string())[..]).is_none());
+    }
+
+    #[test]
+    fn test_resolve_includes() {
+        let results = resolve("Intro\n\n{{include:A}}", DEFAULT_MAX_DEPTH).unwrap();
+
+        let tags = results.iter().map(|result| result.get_property("tag")).collect::<Vec<Value>>();
+
+        assert_eq!(
+            tags,
+            vec![
+                Value::String("p".to_string()),
+                Value::String("h1".to_string()),
+                Value::String("p".to_string())
+            ]
+        );
+
+        assert_eq!(
+            resolve("{{include:Loop}}", DEFAULT_MAX_DEPTH),
+            Err(IncludeError::Cycle(vec!["Loop".to_string(), "Other".to_string(), "Loop".to_string()]))
+        );
+
+        assert_eq!(resolve("{{include:A}}", 1), Err(IncludeError::TooDeep(vec!["A".to_string(), "B".to_string()])));
+        assert_eq!(resolve("{{include:C}}", 1), Err(IncludeError::Loader("Page C not found".to_string())));
+
+        assert_eq!(
+            IncludeError::<String>::Cycle(vec!["A".to_string(), "A".to_string()]).to_string(),
+            "Include cycle: A -> A".to_string()
+        );
+    }
+}
@@ -0,0 +1,126 @@
+// Indented Code Block Detector
+// ------------------------
+// Detects code blocks indented by at least four columns:
+//
+//         fn main() {}
+//
+// Indented code can't interrupt a paragraph, the content is kept verbatim.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, node, class_attributes};
+use crate::markdown::line::{take_line, is_blank, indentation, strip_indentation};
+use crate::types::{Queue, Token};
+
+pub const CODE_INDENTATION: usize = 4;
+
+pub fn detect_indented_code(queue: &mut &[Token]) -> Option<Result> {
+    let mut rest = *queue;
+
+    let mut lines: Vec<Queue> = vec![];
+
+    // Trailing blank lines are not part of the block
+    let mut consumed = 0;
+    let mut code_lines = 0;
+
+    while !rest.is_empty() {
+        let line = take_line(&mut rest);
+
+        if is_blank(&line) {
+            // The block can't start with a blank line
+            if lines.is_empty() {
+                return None;
+            }
+
+            lines.push(strip_indentation(&line, CODE_INDENTATION));
+
+            continue;
+        }
+
+        if indentation(&line) < CODE_INDENTATION {
+            break;
+        }
+
+        lines.push(strip_indentation(&line, CODE_INDENTATION));
+
+        consumed = queue.len() - rest.len();
+        code_lines = lines.len();
+    }
+
+    if consumed == 0 {
+        return None;
+    }
+
+    let mut content: Queue = vec![];
+
+    for line in lines.iter().take(code_lines) {
+        content.extend(line);
+        content.push('\n');
+    }
+
+    *queue = &queue[consumed..];
+
+    Some(
+        node(
+            MarkdownDetector::IndentedCodeDetector,
+            "pre",
+            class_attributes(),
+            None,
+            Some(
+                vec![
+                    Result::new(Detector::RawDetector, Some(content), None, None)
+                ]
+            )
+        )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::Queue;
+
+    use super::*;
+
+    fn indented(input: &str) -> (Option<String>, Option<Result>) {
+        let mut queue = Queue::from_string(input.to_string());
+
+        let (_, consumed, result) = queue.consume(&Detector::MarkdownDetector(MarkdownDetector::IndentedCodeDetector));
+
+        (consumed, result)
+    }
+
+    fn code(result: &Result) -> String {
+        result.children.clone().unwrap()[0].content.clone().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_indented_code_detector() {
+        let (consumed, result) = indented("    a\n\n\tb\n      c\n\nText");
+
+        assert_eq!(consumed.unwrap(), "    a\n\n\tb\n      c\n".to_string());
+        assert_eq!(code(&result.unwrap()), "a\n\nb\n  c\n".to_string());
+
+        let (_, result) = indented("  \tcode");
+
+        assert_eq!(code(&result.unwrap()), "code\n".to_string());
+
+        let (_, result) = indented("   no code");
+
+        assert_eq!(result, None);
+
+        let (_, result) = indented("\n    code");
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_indented_code_does_not_interrupt_paragraph() {
+        let mut queue = Queue::from_string("Text\n    more text\n\n    code".to_string());
+
+        let results = queue.consume_any(&[Detector::MarkdownDetector(MarkdownDetector::IndentedCodeDetector)]).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].content, Some(Queue::from_string("Text\n    more text\n\n".to_string())));
+        assert_eq!(code(&results[1]), "code\n".to_string());
+    }
+}
@@ -0,0 +1,140 @@
+// Inline Extension Detector
+// ------------------------
+// Detects the delimiter runs of the optional inline extensions:
+//
+//     ~~strikethrough~~, ==highlight==, 2^10^ and H~2~O
+//
+// The runs become `del`, `mark`, `sup` and `sub` nodes. Like emphasis, the detector only
+// produces delimiter nodes (with the element they open or close), `resolve_emphasis` matches
+// them afterwards. Opener and closer have to be runs of the same length.
+// A single `~` is a subscript if subscripts are enabled and a strikethrough otherwise.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::MarkdownDetector;
+use crate::types::{Queue, Dict, Value};
+
+/// The enabled inline extensions
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct InlineExtensions {
+    pub strikethrough: bool,
+    pub highlight: bool,
+    pub superscript: bool,
+    pub subscript: bool
+}
+
+impl InlineExtensions {
+    pub fn is_empty(&self) -> bool {
+        !(self.strikethrough || self.highlight || self.superscript || self.subscript)
+    }
+
+    /// The element a delimiter run of the given length opens or closes
+    fn element(&self, delimiter: char, length: usize) -> Option<&'static str> {
+        match (delimiter, length) {
+            ('~', 1) if self.subscript => Some("sub"),
+            ('~', 1 | 2) if self.strikethrough => Some("del"),
+            ('=', 2) if self.highlight => Some("mark"),
+            ('^', 1) if self.superscript => Some("sup"),
+            _ => None
+        }
+    }
+}
+
+pub fn detect_extension_delimiter(queue: &mut Queue, extensions: &InlineExtensions) -> Option<Result> {
+    let delimiter = *queue.first()?;
+
+    if !['~', '=', '^'].contains(&delimiter) {
+        return None;
+    }
+
+    let length = queue.iter().take_while(|token| **token == delimiter).count();
+
+    let element = extensions.element(delimiter, length);
+
+    let run: Queue = queue.drain(0..length).collect();
+
+    // Other runs are text (as a whole, so that their tail isn't taken as a shorter run)
+    let element = match element {
+        Some(element) => element,
+        None => return Some(Result::new(Detector::RawDetector, Some(run), None, None))
+    };
+
+    let properties = Dict::from_values(
+        vec![
+            ("delimiter".to_string(), Value::String(delimiter.to_string())),
+            ("length".to_string(), Value::Integer(length as i64)),
+            ("element".to_string(), Value::String(element.to_string()))
+        ]
+    );
+
+    Some(
+        Result::new(
+            Detector::MarkdownDetector(MarkdownDetector::EmphasisDetector),
+            None,
+            Some(properties),
+            None
+        )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::markdown::emphasis::resolve_emphasis;
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn parse(input: &str, extensions: InlineExtensions) -> Vec<Result> {
+        let mut queue = Queue::from_string(input.to_string());
+
+        let detectors = [Detector::MarkdownDetector(MarkdownDetector::InlineExtensionDetector(extensions))];
+
+        resolve_emphasis(queue.consume_any(&detectors).unwrap())
+    }
+
+    fn tags(results: &[Result]) -> Vec<Value> {
+        results.iter().map(|result| result.get_property("tag")).collect()
+    }
+
+    fn all() -> InlineExtensions {
+        InlineExtensions { strikethrough: true, highlight: true, superscript: true, subscript: true }
+    }
+
+    #[test]
+    fn test_inline_extensions() {
+        let results = parse("~~del~~ ==mark== 2^10^ H~2~O", all());
+
+        assert_eq!(
+            tags(&results),
+            vec![
+                Value::String("del".to_string()),
+                Value::NoneValue,
+                Value::String("mark".to_string()),
+                Value::NoneValue,
+                Value::String("sup".to_string()),
+                Value::NoneValue,
+                Value::String("sub".to_string()),
+                Value::NoneValue
+            ]
+        );
+
+        assert_eq!(results[6].children.clone().unwrap()[0].content, Some(Queue::from_string("2".to_string())));
+    }
+
+    #[test]
+    fn test_inline_extensions_disabled() {
+        let strikethrough = InlineExtensions { strikethrough: true, ..Default::default() };
+
+        // Without subscripts a single tilde is a strikethrough
+        assert_eq!(tags(&parse("~a~", strikethrough.clone())), vec![Value::String("del".to_string())]);
+
+        let results = parse("==a== ^b^ ~~~c~~~ ~~d~", strikethrough);
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].content, Some(Queue::from_string("==a== ^b^ ~~~c~~~ ~~d~".to_string())));
+
+        let results = parse("~~a~~", InlineExtensions { subscript: true, ..Default::default() });
+
+        assert_eq!(results.len(), 1);
+    }
+}
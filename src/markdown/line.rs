@@ -0,0 +1,155 @@
+// Line helpers for the block detectors
+// ------------------------------------
+// Block detectors work on whole lines at the front of the queue.
+// Indentation is measured in columns, tabs advance to the next tab stop (multiple of 4).
+// ------------------------------------
+
+use crate::types::{Queue, Token};
+
+/// Advances the text past the first line (including its line ending) and returns it without the line ending
+pub fn take_line(queue: &mut &[Token]) -> Queue {
+    let text = *queue;
+
+    let (mut line, rest) = match text.iter().position(|token| *token == '\n') {
+        Some(end) => (text[..end].to_vec(), &text[end + 1..]),
+        None => (text.to_vec(), &text[text.len()..])
+    };
+
+    *queue = rest;
+
+    if line.last() == Some(&'\r') {
+        line.pop();
+    }
+
+    line
+}
+
+/// Returns the first line of the text without consuming it
+pub fn peek_line(queue: &[Token]) -> Queue {
+    let mut rest = queue;
+
+    take_line(&mut rest)
+}
+
+/// A line is blank if it only contains spaces or tabs
+pub fn is_blank(line: &[Token]) -> bool {
+    line.iter().all(|token| *token == ' ' || *token == '\t')
+}
+
+pub const TAB_STOP: usize = 4;
+
+/// Width of the leading whitespace in columns
+pub fn indentation(line: &[Token]) -> usize {
+    let mut column = 0;
+
+    for token in line {
+        match token {
+            ' ' => column += 1,
+            '\t' => column += TAB_STOP - column % TAB_STOP,
+            _ => break
+        }
+    }
+
+    column
+}
+
+/// Replaces tabs in the leading whitespace by spaces, the line starts at the given column
+pub fn expand_indentation(line: &[Token], column: usize) -> Queue {
+    let mut expanded: Queue = vec![];
+    let mut current = column;
+
+    for (index, token) in line.iter().enumerate() {
+        match token {
+            ' ' => {
+                expanded.push(' ');
+
+                current += 1;
+            },
+            '\t' => {
+                let width = TAB_STOP - current % TAB_STOP;
+
+                expanded.extend(vec![' '; width]);
+
+                current += width;
+            },
+            _ => {
+                expanded.extend(&line[index..]);
+
+                return expanded;
+            }
+        }
+    }
+
+    expanded
+}
+
+/// Removes up to the given number of columns of indentation
+pub fn strip_indentation(line: &[Token], columns: usize) -> Queue {
+    let expanded = expand_indentation(line, 0);
+
+    let strip = expanded.iter().take(columns).take_while(|token| **token == ' ').count();
+
+    expanded[strip..].to_vec()
+}
+
+/// Removes leading and trailing spaces and tabs
+pub fn trim(line: &[Token]) -> Queue {
+    let start = line.iter().position(|token| *token != ' ' && *token != '\t');
+
+    match start {
+        Some(start) => {
+            let end = line.iter().rposition(|token| *token != ' ' && *token != '\t').unwrap();
+
+            line[start..=end].to_vec()
+        },
+        None => vec![]
+    }
+}
+
+/// Removes the common indentation of the non-blank lines
+pub fn dedent(text: &[Token]) -> Queue {
+    let lines = text.split(|token| *token == '\n').collect::<Vec<&[Token]>>();
+
+    let common = lines.iter()
+        .filter(|line| !is_blank(line))
+        .map(|line| indentation(line))
+        .min()
+        .unwrap_or(0);
+
+    lines.iter().map(|line| strip_indentation(line, common)).collect::<Vec<Queue>>().join(&'\n')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::base::Consumable;
+    use crate::types::Queue;
+
+    use super::*;
+
+    #[test]
+    fn test_indentation() {
+        assert_eq!(indentation(&Queue::from_string("  text".to_string())), 2);
+        assert_eq!(indentation(&Queue::from_string("\ttext".to_string())), 4);
+        assert_eq!(indentation(&Queue::from_string("  \ttext".to_string())), 4);
+        assert_eq!(indentation(&Queue::from_string("    \ttext".to_string())), 8);
+    }
+
+    #[test]
+    fn test_strip_indentation() {
+        let line = Queue::from_string("\t\tcode".to_string());
+
+        assert_eq!(strip_indentation(&line, 4), Queue::from_string("    code".to_string()));
+
+        let line = Queue::from_string(" \tcode".to_string());
+
+        assert_eq!(strip_indentation(&line, 2), Queue::from_string("  code".to_string()));
+        assert_eq!(expand_indentation(&Queue::from_string("\tcode".to_string()), 2), Queue::from_string("  code".to_string()));
+    }
+
+    #[test]
+    fn test_dedent() {
+        let text = Queue::from_string("\n    a\n\n      b\n".to_string());
+
+        assert_eq!(dedent(&text), Queue::from_string("\na\n\n  b\n".to_string()));
+    }
+}
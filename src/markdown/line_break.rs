@@ -0,0 +1,89 @@
+// Line Break Detector
+// ------------------------
+// Detects hard line breaks, two or more spaces or a backslash at the end of a line:
+//
+//     First line··
+//     Second line\
+//     Third line
+//
+// The spaces or the backslash, the line ending and the indentation of the next line are
+// replaced by a `br` node.
+// A break at the end of a paragraph (before a blank line or another block) is ignored.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, interrupts_paragraph, node, class_attributes};
+use crate::markdown::line::{peek_line, is_blank};
+use crate::types::Queue;
+
+pub const MIN_BREAK_SPACES: usize = 2;
+
+pub fn detect_line_break(queue: &mut Queue) -> Option<Result> {
+    let length = match queue.first()? {
+        '\\' => 1,
+        ' ' => queue.iter().take_while(|token| **token == ' ').count(),
+        _ => return None
+    };
+
+    if queue.first() == Some(&' ') && length < MIN_BREAK_SPACES {
+        return None;
+    }
+
+    if queue.get(length) != Some(&'\n') {
+        return None;
+    }
+
+    // The break has to be followed by another line of the same paragraph
+    let next = peek_line(&queue[length + 1..]);
+
+    if is_blank(&next) || interrupts_paragraph(&next) {
+        return None;
+    }
+
+    // Leading whitespace of the next line is removed as well
+    let indent = queue[length + 1..].iter().take_while(|token| **token == ' ' || **token == '\t').count();
+
+    queue.drain(0..length + 1 + indent);
+
+    Some(node(MarkdownDetector::LineBreakDetector, "br", class_attributes(), None, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn texts(input: &str) -> Vec<Value> {
+        let mut queue = Queue::from_string(input.to_string());
+
+        let results = queue.consume_any(&[Detector::MarkdownDetector(MarkdownDetector::LineBreakDetector)]).unwrap();
+
+        results.iter().map(|result| match result.detector {
+            Detector::RawDetector => Value::String(result.content.clone().unwrap().to_string()),
+            _ => result.get_property("tag")
+        }).collect()
+    }
+
+    #[test]
+    fn test_line_break() {
+        assert_eq!(
+            texts("foo   \nbar\\\n  baz"),
+            vec![
+                Value::String("foo".to_string()),
+                Value::String("br".to_string()),
+                Value::String("bar".to_string()),
+                Value::String("br".to_string()),
+                Value::String("baz".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_line_break_end_of_paragraph() {
+        assert_eq!(texts("foo \nbar").len(), 1);
+        assert_eq!(texts("foo  \n\nbar").len(), 1);
+        assert_eq!(texts("foo\\\n# heading").len(), 1);
+        assert_eq!(texts("foo  ").len(), 1);
+    }
+}
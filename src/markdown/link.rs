@@ -0,0 +1,358 @@
+// Link and Image Detectors
+// ------------------------
+// Detects inline links and images:
+//
+//     [text](https://example.com "Title")
+//     ![alt](image.png 'Title')
+//
+// Destinations can be wrapped in `<...>` (then they may contain spaces) or contain balanced
+// parentheses, titles are wrapped in `"`, `'` or `(...)`. Both are kept in their source form,
+// escapes and character references are resolved by `decode_references`.
+// The nodes have the same shape as the ones of the HTML `a` and `img` detectors.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, node, class_attributes};
+use crate::types::{Queue, Token, Dict, Value};
+
+#[derive(Debug, Clone)]
+pub struct InlineLink {
+    pub text: Queue,
+    pub destination: Queue,
+    pub title: Option<Queue>,
+    pub length: usize // Number of tokens the link takes in the queue
+}
+
+fn backtick_run(queue: &[Token], start: usize) -> usize {
+    queue[start..].iter().take_while(|token| **token == '`').count()
+}
+
+/// Returns the index of the `]` closing the bracket at `start` (code spans and escapes are skipped)
+pub fn closing_bracket(queue: &[Token], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut index = start;
+
+    while index < queue.len() {
+        match queue[index] {
+            '\\' => index += 1,
+            '`' => {
+                let length = backtick_run(queue, index);
+
+                // Skip the whole code span (or only the run if it is not closed)
+                let mut end = index + length;
+                let mut closed = false;
+
+                while end < queue.len() {
+                    let run = backtick_run(queue, end);
+
+                    if run == length {
+                        closed = true;
+
+                        break;
+                    }
+
+                    end += run.max(1);
+                }
+
+                index = if closed { end + length } else { index + length };
+
+                continue;
+            },
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+
+                if depth == 0 {
+                    return Some(index);
+                }
+            },
+            _ => {}
+        }
+
+        index += 1;
+    }
+
+    None
+}
+
+pub fn skip_whitespace(queue: &[Token], mut index: usize) -> usize {
+    let mut newline = false;
+
+    while let Some(token) = queue.get(index) {
+        match token {
+            ' ' | '\t' => {},
+            // The destination and title may be on the next line, but not after a blank line
+            '\n' if !newline => newline = true,
+            _ => break
+        }
+
+        index += 1;
+    }
+
+    index
+}
+
+/// Parses a link destination starting at `start`, returns it and the index after it
+pub fn link_destination(queue: &[Token], start: usize) -> Option<(Queue, usize)> {
+    let mut index = start;
+
+    if queue.get(index) == Some(&'<') {
+        index += 1;
+
+        while let Some(token) = queue.get(index) {
+            match token {
+                '>' => return Some((queue[start + 1..index].to_vec(), index + 1)),
+                '<' | '\n' => return None,
+                '\\' => index += 2,
+                _ => index += 1
+            }
+        }
+
+        return None;
+    }
+
+    let mut depth = 0;
+
+    while let Some(token) = queue.get(index) {
+        match token {
+            '\\' if queue.get(index + 1).is_some_and(|token| token.is_ascii_punctuation()) => index += 1,
+            '(' => depth += 1,
+            ')' if depth == 0 => break,
+            ')' => depth -= 1,
+            token if token.is_whitespace() || token.is_control() => break,
+            _ => {}
+        }
+
+        index += 1;
+    }
+
+    if depth != 0 {
+        return None;
+    }
+
+    Some((queue[start..index].to_vec(), index))
+}
+
+/// Parses a link title starting at `start`, returns it and the index after it
+pub fn link_title(queue: &[Token], start: usize) -> Option<(Queue, usize)> {
+    let closing = match queue.get(start)? {
+        '"' => '"',
+        '\'' => '\'',
+        '(' => ')',
+        _ => return None
+    };
+
+    let mut index = start + 1;
+
+    while let Some(token) = queue.get(index) {
+        match token {
+            '\\' => index += 1,
+            token if *token == closing => return Some((queue[start + 1..index].to_vec(), index + 1)),
+            '(' if closing == ')' => return None,
+            _ => {}
+        }
+
+        index += 1;
+    }
+
+    None
+}
+
+/// Parses `[text](destination "title")` (without the `!` of an image) at the front of the queue
+pub fn inline_link(queue: &[Token]) -> Option<InlineLink> {
+    if queue.first() != Some(&'[') {
+        return None;
+    }
+
+    let end = closing_bracket(queue, 0)?;
+
+    if queue.get(end + 1) != Some(&'(') {
+        return None;
+    }
+
+    let mut index = skip_whitespace(queue, end + 2);
+
+    let (destination, after) = if queue.get(index) == Some(&')') {
+        (vec![], index)
+    } else {
+        link_destination(queue, index)?
+    };
+
+    index = skip_whitespace(queue, after);
+
+    let mut title = None;
+
+    // The title has to be separated from the destination by whitespace
+    if index > after || destination.is_empty() {
+        if let Some((text, after)) = link_title(queue, index) {
+            title = Some(text);
+
+            index = skip_whitespace(queue, after);
+        }
+    }
+
+    if queue.get(index) != Some(&')') {
+        return None;
+    }
+
+    Some(
+        InlineLink {
+            text: queue[1..end].to_vec(),
+            destination,
+            title,
+            length: index + 1
+        }
+    )
+}
+
+/// Links can't contain other links
+pub fn contains_link(text: &[Token]) -> bool {
+    (0..text.len()).any(|index| {
+        text[index] == '[' &&
+        (index == 0 || (text[index - 1] != '\\' && text[index - 1] != '!')) &&
+        inline_link(&text[index..]).is_some()
+    })
+}
+
+fn attributes(href_key: &str, destination: Queue, title: Option<Queue>) -> Dict {
+    let mut attributes = class_attributes();
+
+    attributes.set(href_key, Value::String(destination.iter().collect()));
+
+    if let Some(title) = title {
+        attributes.set("title", Value::String(title.iter().collect()));
+    }
+
+    attributes
+}
+
+/// Builds an `a` node with the same shape as the HTML link detector
+pub fn link_result(detector: MarkdownDetector, text: Queue, destination: Queue, title: Option<Queue>) -> Result {
+    node(detector, "a", attributes("href", destination, title), Some(text), None)
+}
+
+/// Builds an `img` node with the same shape as the HTML image detector
+pub fn image_result(detector: MarkdownDetector, alt: Queue, source: Queue, title: Option<Queue>) -> Result {
+    let mut attributes = attributes("src", source, title);
+
+    attributes.set("alt", Value::String(alt.iter().collect()));
+
+    node(detector, "img", attributes, None, None)
+}
+
+pub fn detect_link(queue: &mut Queue) -> Option<Result> {
+    let link = inline_link(queue)?;
+
+    if contains_link(&link.text) {
+        return None;
+    }
+
+    queue.drain(0..link.length);
+
+    Some(
+        link_result(MarkdownDetector::LinkDetector, link.text, link.destination, link.title)
+    )
+}
+
+pub fn detect_image(queue: &mut Queue) -> Option<Result> {
+    if queue.first() != Some(&'!') {
+        return None;
+    }
+
+    let link = inline_link(&queue[1..])?;
+
+    queue.drain(0..link.length + 1);
+
+    Some(
+        image_result(MarkdownDetector::ImageDetector, link.text, link.destination, link.title)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn detect(detector: MarkdownDetector, input: &str) -> Option<(String, Result)> {
+        let mut queue = Queue::from_string(input.to_string());
+
+        let (_, consumed, result) = queue.consume(&Detector::MarkdownDetector(detector));
+
+        Some((consumed?, result?))
+    }
+
+    fn attribute(result: &Result, key: &str) -> Value {
+        match result.get_property("attributes") {
+            Value::Dict(attributes) => attributes.get(key),
+            _ => Value::NoneValue
+        }
+    }
+
+    #[test]
+    fn test_link() {
+        let (consumed, result) = detect(MarkdownDetector::LinkDetector, "[a *b*](/url \"Title\") rest").unwrap();
+
+        assert_eq!(consumed, "[a *b*](/url \"Title\")".to_string());
+        assert_eq!(result.get_property("tag"), Value::String("a".to_string()));
+        assert_eq!(result.content, Some(Queue::from_string("a *b*".to_string())));
+        assert_eq!(attribute(&result, "href"), Value::String("/url".to_string()));
+        assert_eq!(attribute(&result, "title"), Value::String("Title".to_string()));
+
+        let (_, result) = detect(MarkdownDetector::LinkDetector, "[link]()").unwrap();
+
+        assert_eq!(attribute(&result, "href"), Value::String("".to_string()));
+        assert_eq!(attribute(&result, "title"), Value::NoneValue);
+    }
+
+    #[test]
+    fn test_link_destination() {
+        let (_, result) = detect(MarkdownDetector::LinkDetector, "[a](foo(and(bar)))").unwrap();
+
+        assert_eq!(attribute(&result, "href"), Value::String("foo(and(bar))".to_string()));
+
+        let (_, result) = detect(MarkdownDetector::LinkDetector, "[a](<my url> 'it\\'s')").unwrap();
+
+        assert_eq!(attribute(&result, "href"), Value::String("my url".to_string()));
+        assert_eq!(attribute(&result, "title"), Value::String("it\\'s".to_string()));
+
+        let (_, result) = detect(MarkdownDetector::LinkDetector, "[a](foo\\)bar)").unwrap();
+
+        // Escapes are kept in their source form
+        assert_eq!(attribute(&result, "href"), Value::String("foo\\)bar".to_string()));
+
+        // Without whitespace the title is part of the destination
+        let (_, result) = detect(MarkdownDetector::LinkDetector, "[a](/url\"title\")").unwrap();
+
+        assert_eq!(attribute(&result, "href"), Value::String("/url\"title\"".to_string()));
+
+        assert!(detect(MarkdownDetector::LinkDetector, "[a](foo(bar)").is_none());
+        assert!(detect(MarkdownDetector::LinkDetector, "[a](my url)").is_none());
+    }
+
+    #[test]
+    fn test_link_text() {
+        let (_, result) = detect(MarkdownDetector::LinkDetector, "[a [b] c](/url)").unwrap();
+
+        assert_eq!(result.content, Some(Queue::from_string("a [b] c".to_string())));
+
+        // Code spans bind tighter than links, links can't be nested
+        assert!(detect(MarkdownDetector::LinkDetector, "[not a `link](/foo`)").is_none());
+        assert!(detect(MarkdownDetector::LinkDetector, "[a [b](/inner)](/outer)").is_none());
+        assert!(detect(MarkdownDetector::LinkDetector, "[a \\](/url)").is_none());
+    }
+
+    #[test]
+    fn test_image() {
+        let (consumed, result) = detect(MarkdownDetector::ImageDetector, "![a \\*b\\*](/img.png \"T\")!").unwrap();
+
+        assert_eq!(consumed, "![a \\*b\\*](/img.png \"T\")".to_string());
+        assert_eq!(result.get_property("tag"), Value::String("img".to_string()));
+        assert_eq!(attribute(&result, "src"), Value::String("/img.png".to_string()));
+        assert_eq!(attribute(&result, "alt"), Value::String("a \\*b\\*".to_string()));
+        assert_eq!(attribute(&result, "title"), Value::String("T".to_string()));
+        assert_eq!(result.content, None);
+
+        assert!(detect(MarkdownDetector::ImageDetector, "[a](/img.png)").is_none());
+    }
+}
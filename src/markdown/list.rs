@@ -0,0 +1,523 @@
+// List Detector
+// ------------------------
+// Detects bullet lists (`-`, `*`, `+`) and ordered lists (`1.` or `1)`):
+//
+//     1. First
+//     2. Second
+//        - Nested
+//
+// Each item's content is stripped of its indentation and parsed as blocks again.
+// A list is loose if its items are separated by blank lines or an item contains
+// blocks separated by blank lines, otherwise it is tight. This is exposed as the
+// `tight` attribute of the `ul`/`ol` and `li` nodes.
+//
+// Items starting with `[ ]` or `[x]` followed by whitespace and text are tasks and get a
+// `checked` attribute:
+//
+//     - [x] Done
+//     - [ ] Open
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, interrupts_paragraph, node, class_attributes};
+use crate::markdown::fenced_code::is_fence;
+use crate::markdown::thematic_break::is_thematic_break;
+use crate::markdown::line::{take_line, peek_line, is_blank, indentation, expand_indentation, strip_indentation};
+use crate::types::{Queue, Token, Dict, Value};
+
+#[derive(Debug, Clone)]
+pub struct ListMarker {
+    pub ordered: bool,
+    pub delimiter: Token, // The bullet character or `.`/`)` of an ordered list
+    pub start: i64,
+    pub content_column: usize,
+    pub content: Queue // The content on the line of the marker
+}
+
+impl ListMarker {
+    fn same_list(&self, other: &ListMarker) -> bool {
+        self.ordered == other.ordered && self.delimiter == other.delimiter
+    }
+}
+
+/// Detects a list item marker at the start of the line
+pub fn list_marker(line: &[Token]) -> Option<ListMarker> {
+    let indent = indentation(line);
+
+    // Thematic breaks take precedence over list items (`- - -`)
+    if indent > 3 || is_thematic_break(line) {
+        return None;
+    }
+
+    let digits = line[indent..].iter().take_while(|token| token.is_ascii_digit()).count();
+
+    let (ordered, delimiter, start, width) = if digits > 0 {
+        if digits > 9 {
+            return None;
+        }
+
+        let delimiter = *line.get(indent + digits)?;
+
+        if delimiter != '.' && delimiter != ')' {
+            return None;
+        }
+
+        let start = line[indent..indent + digits].iter().collect::<String>().parse::<i64>().ok()?;
+
+        (true, delimiter, start, digits + 1)
+    } else {
+        let bullet = *line.get(indent)?;
+
+        if !['-', '*', '+'].contains(&bullet) {
+            return None;
+        }
+
+        (false, bullet, 1, 1)
+    };
+
+    let rest = &line[indent + width..];
+
+    // The marker has to be followed by whitespace or the end of the line
+    if !rest.is_empty() && rest[0] != ' ' && rest[0] != '\t' {
+        return None;
+    }
+
+    let expanded = expand_indentation(rest, indent + width);
+
+    let spaces = indentation(&expanded);
+
+    // An empty first line or indented code after the marker only take one column
+    let padding = if is_blank(&expanded) || spaces > 4 { 1 } else { spaces };
+
+    let content = if is_blank(&expanded) { vec![] } else { expanded[padding..].to_vec() };
+
+    Some(
+        ListMarker {
+            ordered,
+            delimiter,
+            start,
+            content_column: indent + width + padding,
+            content
+        }
+    )
+}
+
+/// Whether the line starts a list item
+pub fn is_list_item(line: &[Token]) -> bool {
+    list_marker(line).is_some()
+}
+
+/// Only non-empty bullet items or ordered items starting at 1 can interrupt a paragraph
+pub fn can_interrupt_paragraph(line: &[Token]) -> bool {
+    match list_marker(line) {
+        Some(marker) => !is_blank(&marker.content) && (!marker.ordered || marker.start == 1),
+        None => false
+    }
+}
+
+/// Whether blank lines separate blocks directly inside the item (nested lists and code are ignored)
+fn is_loose_item(lines: &[Queue]) -> bool {
+    // An empty line of the marker does not separate blocks
+    let lines = match lines.split_first() {
+        Some((first, rest)) if is_blank(first) => rest,
+        _ => lines
+    };
+
+    let mut blank = false;
+    let mut in_fence = false;
+    let mut in_sublist = false;
+
+    for line in lines {
+        if in_fence {
+            in_fence = !is_fence(line);
+
+            continue;
+        }
+
+        if is_blank(line) {
+            blank = true;
+
+            continue;
+        }
+
+        let nested = indentation(line) > 0;
+
+        if blank && !(in_sublist && (nested || is_list_item(line))) {
+            return true;
+        }
+
+        blank = false;
+
+        if !nested {
+            if is_list_item(line) {
+                in_sublist = true;
+            } else if interrupts_paragraph(line) {
+                in_sublist = false;
+            }
+        }
+
+        in_fence = is_fence(line);
+    }
+
+    false
+}
+
+/// Returns whether the task is checked and the content without the task marker
+pub fn task_marker(content: &[Token]) -> Option<(bool, Queue)> {
+    if content.len() < 3 || content[0] != '[' || content[2] != ']' {
+        return None;
+    }
+
+    let checked = match content[1] {
+        ' ' => false,
+        'x' | 'X' => true,
+        _ => return None
+    };
+
+    // A task needs text after the marker
+    match content.get(3) {
+        Some(' ') | Some('\t') if !is_blank(&content[4..]) => Some((checked, content[4..].to_vec())),
+        _ => None
+    }
+}
+
+/// Collects all task items of the result tree as `text`, `checked` and (1-based) `line`
+pub fn collect_tasks(results: &[Result]) -> Vec<Value> {
+    let mut tasks = vec![];
+
+    collect_tasks_from(results, 0, &mut tasks);
+
+    tasks
+}
+
+fn collect_tasks_from(results: &[Result], offset: i64, tasks: &mut Vec<Value>) {
+    for result in results {
+        // Lines are relative to the content of the parent
+        let line = offset + match result.get_property("line") {
+            Value::Integer(line) => line,
+            _ => 0
+        };
+
+        if result.detector == Detector::MarkdownDetector(MarkdownDetector::ListDetector) {
+            if let Value::Dict(attributes) = result.get_property("attributes") {
+                if let Value::Boolean(checked) = attributes.get("checked") {
+                    let content = result.content.clone().unwrap_or_default();
+
+                    let text = content.to_string().lines().next().unwrap_or_default().trim().to_string();
+
+                    tasks.push(
+                        Value::Dict(
+                            Dict::from_values(
+                                vec![
+                                    ("text".to_string(), Value::String(text)),
+                                    ("checked".to_string(), Value::Boolean(checked)),
+                                    ("line".to_string(), Value::Integer(line + 1))
+                                ]
+                            )
+                        )
+                    );
+                }
+            }
+        }
+
+        if let Some(children) = &result.children {
+            collect_tasks_from(children, line, tasks);
+        }
+    }
+}
+
+fn list_attributes(tight: bool) -> Dict {
+    let mut attributes = class_attributes();
+
+    attributes.set("tight", Value::Boolean(tight));
+
+    attributes
+}
+
+pub fn detect_list(queue: &mut &[Token]) -> Option<Result> {
+    let first = list_marker(&peek_line(queue))?;
+
+    let mut rest = *queue;
+
+    // Trailing blank lines are not part of the list
+    let mut consumed = 0;
+
+    let mut items: Vec<Vec<Queue>> = vec![];
+    let mut current: Option<ListMarker> = None;
+
+    // Line of every item relative to the start of the list
+    let mut item_lines: Vec<i64> = vec![];
+    let mut line_number = 0;
+
+    let mut blank_lines = 0;
+    let mut blank_between_items = false;
+
+    // An item can start with at most one blank line
+    let mut closed = false;
+
+    while !rest.is_empty() {
+        let line = peek_line(rest);
+
+        if is_blank(&line) {
+            if let (Some(lines), Some(_)) = (items.last(), &current) {
+                if lines.len() == 1 && is_blank(&lines[0]) {
+                    closed = true;
+                }
+            }
+
+            blank_lines += 1;
+
+            if let Some(lines) = items.last_mut() {
+                lines.push(vec![]);
+            }
+
+            take_line(&mut rest);
+
+            line_number += 1;
+
+            continue;
+        }
+
+        let marker = list_marker(&line);
+
+        match &current {
+            Some(item) if !closed && indentation(&line) >= item.content_column => {
+                // Continuation of the current item
+                items.last_mut().unwrap().push(strip_indentation(&line, item.content_column));
+            },
+            _ if marker.as_ref().is_some_and(|marker| marker.same_list(&first)) => {
+                // The next item of the list
+                if blank_lines > 0 && current.is_some() {
+                    blank_between_items = true;
+                }
+
+                let marker = marker.unwrap();
+
+                items.push(vec![marker.content.clone()]);
+                item_lines.push(line_number);
+
+                current = Some(marker);
+                closed = false;
+            },
+            Some(_) => {
+                // Lazy continuation of paragraph text, an item of another list ends the list
+                let last = items.last().and_then(|lines| lines.last()).cloned().unwrap_or_default();
+
+                if marker.is_some() || closed || blank_lines > 0 || is_blank(&last) || interrupts_paragraph(&last) || interrupts_paragraph(&line) {
+                    break;
+                }
+
+                items.last_mut().unwrap().push(line);
+            },
+            None => break
+        }
+
+        blank_lines = 0;
+
+        take_line(&mut rest);
+
+        line_number += 1;
+
+        consumed = queue.len() - rest.len();
+    }
+
+    *queue = &queue[consumed..];
+
+    // Remove the trailing blank lines of every item
+    for lines in items.iter_mut() {
+        while lines.len() > 1 && lines.last().is_some_and(|line| is_blank(line)) {
+            lines.pop();
+        }
+    }
+
+    let tight = !blank_between_items && !items.iter().any(|lines| is_loose_item(lines));
+
+    let children = items.iter().zip(item_lines).map(|(lines, line)| {
+        let mut lines = lines.clone();
+
+        let mut attributes = list_attributes(tight);
+
+        if let Some((checked, content)) = task_marker(&lines[0]) {
+            attributes.set("checked", Value::Boolean(checked));
+
+            lines[0] = content;
+        }
+
+        let mut content: Queue = vec![];
+
+        for line in lines {
+            content.extend(line);
+            content.push('\n');
+        }
+
+        let mut item = node(MarkdownDetector::ListDetector, "li", attributes, Some(content), None);
+
+        item.set_property("line", Value::Integer(line));
+
+        item
+    }).collect::<Vec<Result>>();
+
+    let mut attributes = list_attributes(tight);
+
+    if first.ordered {
+        attributes.set("start", Value::Integer(first.start));
+    }
+
+    let mut result = node(MarkdownDetector::ListDetector, if first.ordered { "ol" } else { "ul" }, attributes, None, Some(children));
+
+    result.set_property("ordered", Value::Boolean(first.ordered));
+    result.set_property("delimiter", Value::String(first.delimiter.to_string()));
+
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn list(input: &str) -> (String, Result) {
+        let mut queue = Queue::from_string(input.to_string());
+
+        let (_, consumed, result) = queue.consume(&Detector::MarkdownDetector(MarkdownDetector::ListDetector));
+
+        (consumed.unwrap(), result.unwrap())
+    }
+
+    fn attribute(result: &Result, key: &str) -> Value {
+        match result.get_property("attributes") {
+            Value::Dict(attributes) => attributes.get(key),
+            _ => Value::NoneValue
+        }
+    }
+
+    fn items(result: &Result) -> Vec<String> {
+        result.children.clone().unwrap().iter().map(|item| item.content.clone().unwrap().to_string()).collect()
+    }
+
+    #[test]
+    fn test_list_marker() {
+        let marker = list_marker(&Queue::from_string("- item".to_string())).unwrap();
+
+        assert!(!marker.ordered);
+        assert_eq!(marker.content_column, 2);
+
+        let marker = list_marker(&Queue::from_string(" 12)   item".to_string())).unwrap();
+
+        assert!(marker.ordered);
+        assert_eq!(marker.start, 12);
+        assert_eq!(marker.content_column, 7);
+
+        let marker = list_marker(&Queue::from_string("-      code".to_string())).unwrap();
+
+        assert_eq!(marker.content_column, 2);
+        assert_eq!(marker.content, Queue::from_string("     code".to_string()));
+
+        assert!(list_marker(&Queue::from_string("-no space".to_string())).is_none());
+        assert!(list_marker(&Queue::from_string("1234567890. too long".to_string())).is_none());
+    }
+
+    #[test]
+    fn test_list_detector() {
+        let (consumed, result) = list("- one\n- two\n  continued\n+ other list");
+
+        assert_eq!(consumed, "- one\n- two\n  continued\n".to_string());
+        assert_eq!(result.get_property("tag"), Value::String("ul".to_string()));
+        assert_eq!(items(&result), vec!["one\n".to_string(), "two\ncontinued\n".to_string()]);
+        assert_eq!(attribute(&result, "tight"), Value::Boolean(true));
+
+        let (_, result) = list("3) three\n4) four\nlazy");
+
+        assert_eq!(result.get_property("tag"), Value::String("ol".to_string()));
+        assert_eq!(attribute(&result, "start"), Value::Integer(3));
+        assert_eq!(items(&result), vec!["three\n".to_string(), "four\nlazy\n".to_string()]);
+
+        // An item of another list is not lazy text
+        let (consumed, _) = list("1. one\n2) two");
+
+        assert_eq!(consumed, "1. one\n".to_string());
+    }
+
+    #[test]
+    fn test_list_nested() {
+        let (_, result) = list("1. one\n   - nested\n\n   - nested loose\n2. two");
+
+        assert_eq!(items(&result), vec!["one\n- nested\n\n- nested loose\n".to_string(), "two\n".to_string()]);
+        assert_eq!(attribute(&result, "tight"), Value::Boolean(true));
+    }
+
+    #[test]
+    fn test_list_loose() {
+        let (consumed, result) = list("- one\n\n- two\n\n\nText");
+
+        assert_eq!(consumed, "- one\n\n- two\n".to_string());
+        assert_eq!(attribute(&result, "tight"), Value::Boolean(false));
+        assert_eq!(attribute(&result.children.clone().unwrap()[0], "tight"), Value::Boolean(false));
+
+        let (_, result) = list("- one\n\n  paragraph");
+
+        assert_eq!(attribute(&result, "tight"), Value::Boolean(false));
+
+        // The empty line of the marker is not a blank line
+        let (_, result) = list("-\n  foo");
+
+        assert_eq!(items(&result), vec!["\nfoo\n".to_string()]);
+        assert_eq!(attribute(&result, "tight"), Value::Boolean(true));
+    }
+
+    #[test]
+    fn test_task_list() {
+        let (_, result) = list("- [ ] open\n- [x] done\n- [y] no task\n- [ ]no task\n- [x]\n- [ ]  ");
+
+        let children = result.children.clone().unwrap();
+
+        assert_eq!(attribute(&children[0], "checked"), Value::Boolean(false));
+        assert_eq!(attribute(&children[1], "checked"), Value::Boolean(true));
+        assert_eq!(attribute(&children[2], "checked"), Value::NoneValue);
+        assert_eq!(attribute(&children[3], "checked"), Value::NoneValue);
+        assert_eq!(attribute(&children[4], "checked"), Value::NoneValue);
+        assert_eq!(attribute(&children[5], "checked"), Value::NoneValue);
+        assert_eq!(items(&result)[0], "open\n".to_string());
+        assert_eq!(children[1].get_property("line"), Value::Integer(1));
+    }
+
+    #[test]
+    fn test_collect_tasks() {
+        let mut queue = Queue::from_string("# Todo\n\n- [ ] first\n- item\n  > - [x] quoted\n".to_string());
+
+        let results = queue.consume_any(
+            &[
+                Detector::MarkdownDetector(MarkdownDetector::ListDetector),
+                Detector::MarkdownDetector(MarkdownDetector::BlockquoteDetector)
+            ]
+        ).unwrap();
+
+        let tasks = collect_tasks(&results);
+
+        assert_eq!(tasks.len(), 2);
+
+        match (&tasks[0], &tasks[1]) {
+            (Value::Dict(first), Value::Dict(second)) => {
+                assert_eq!(first.get("text"), Value::String("first".to_string()));
+                assert_eq!(first.get("checked"), Value::Boolean(false));
+                assert_eq!(first.get("line"), Value::Integer(3));
+                assert_eq!(second.get("text"), Value::String("quoted".to_string()));
+                assert_eq!(second.get("checked"), Value::Boolean(true));
+                assert_eq!(second.get("line"), Value::Integer(5));
+            },
+            _ => panic!("Tasks should be dicts")
+        }
+    }
+
+    #[test]
+    fn test_list_interrupts_paragraph() {
+        let mut queue = Queue::from_string("Text\n2. no list\n- list".to_string());
+
+        let results = queue.consume_any(&[Detector::MarkdownDetector(MarkdownDetector::ListDetector)]).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].content, Some(Queue::from_string("Text\n2. no list\n".to_string())));
+        assert_eq!(results[1].get_property("tag"), Value::String("ul".to_string()));
+    }
+}
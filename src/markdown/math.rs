@@ -0,0 +1,237 @@
+// Math Detectors
+// ------------------------
+// Detects TeX math in dollar signs:
+//
+//     Inline $e^{i\pi} + 1 = 0$ or display $$\sum_i x_i$$
+//
+//     $$
+//     E = mc^2
+//     $$
+//
+// The results are `math` nodes with a `display` attribute, the TeX source is kept verbatim
+// and never parsed again. To avoid false positives on currency (`$5 and $10`), an opening
+// `$` has to be followed by a non-space character and a closing `$` has to be preceded by a
+// non-space character and must not be followed by a digit. Inline math can't contain other
+// (unescaped) dollar signs and no math can contain blank lines.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, node, class_attributes};
+use crate::markdown::line::{take_line, indentation, trim, is_blank};
+use crate::types::{Queue, Token, Value};
+
+fn math_result(detector: MarkdownDetector, source: Queue, display: bool) -> Result {
+    let mut attributes = class_attributes();
+
+    attributes.set("display", Value::Boolean(display));
+
+    node(
+        detector,
+        "math",
+        attributes,
+        None,
+        Some(
+            vec![
+                Result::new(Detector::RawDetector, Some(source), None, None)
+            ]
+        )
+    )
+}
+
+/// Index of the closing delimiter (of `length` dollar signs) of math starting at `start`
+fn closing_delimiter(queue: &[Token], start: usize, length: usize) -> Option<usize> {
+    let mut index = start;
+
+    while index < queue.len() {
+        match queue[index] {
+            // Escaped dollar signs don't close the math
+            '\\' => index += 1,
+            '\n' if queue[index + 1..].iter().take_while(|token| **token != '\n').all(|token| token.is_whitespace()) => return None,
+            '$' => {
+                let run = queue[index..].iter().take_while(|token| **token == '$').count();
+
+                let closes = run == length &&
+                    !queue[index - 1].is_whitespace() &&
+                    !queue.get(index + run).is_some_and(|token| token.is_ascii_digit());
+
+                if closes {
+                    return Some(index);
+                }
+
+                // Like in TeX, a dollar sign can't appear in inline math (`$5 and $10, x$` is text)
+                if length == 1 {
+                    return None;
+                }
+
+                index += run;
+
+                continue;
+            },
+            _ => {}
+        }
+
+        index += 1;
+    }
+
+    None
+}
+
+pub fn detect_math(queue: &mut Queue) -> Option<Result> {
+    let length = queue.iter().take_while(|token| **token == '$').count();
+
+    if length == 0 {
+        return None;
+    }
+
+    let end = if length <= 2 && queue.get(length).is_some_and(|token| !token.is_whitespace()) {
+        closing_delimiter(queue, length, length)
+    } else {
+        None
+    };
+
+    let end = match end {
+        Some(end) => end,
+        None => {
+            // Without a closing delimiter the dollar signs are literal text
+            let literal: Queue = queue.drain(0..length).collect();
+
+            return Some(
+                Result::new(Detector::RawDetector, Some(literal), None, None)
+            );
+        }
+    };
+
+    let source: Queue = queue[length..end].to_vec();
+
+    queue.drain(0..end + length);
+
+    Some(math_result(MarkdownDetector::MathDetector, source, length == 2))
+}
+
+fn ends_with_delimiter(line: &[Token]) -> bool {
+    line.ends_with(&['$', '$']) && !line.ends_with(&['\\', '$', '$'])
+}
+
+pub fn detect_math_block(queue: &mut &[Token]) -> Option<Result> {
+    let mut rest = *queue;
+
+    let first = take_line(&mut rest);
+
+    if indentation(&first) > 3 {
+        return None;
+    }
+
+    let first = trim(&first);
+
+    if !first.starts_with(&['$', '$']) {
+        return None;
+    }
+
+    let mut lines: Vec<Queue> = vec![trim(&first[2..])];
+
+    // Text after the closing delimiter on the opening line makes it inline math
+    if lines[0].windows(2).any(|window| window == ['$', '$']) && !ends_with_delimiter(&lines[0]) {
+        return None;
+    }
+
+    loop {
+        let line = lines.last()?;
+
+        if ends_with_delimiter(line) && (lines.len() > 1 || line.len() > 2) {
+            let (last, length) = (lines.len() - 1, line.len());
+
+            lines[last] = trim(&lines[last][..length - 2]);
+
+            break;
+        }
+
+        if rest.is_empty() {
+            return None;
+        }
+
+        let line = take_line(&mut rest);
+
+        if is_blank(&line) {
+            return None;
+        }
+
+        lines.push(trim(&line));
+    }
+
+    // Leading and trailing empty lines are not part of the source
+    let start = lines.iter().position(|line| !line.is_empty())?;
+    let end = lines.iter().rposition(|line| !line.is_empty())?;
+
+    let source = lines[start..=end].join(&'\n');
+
+    let consumed = queue.len() - rest.len();
+
+    *queue = &queue[consumed..];
+
+    Some(math_result(MarkdownDetector::MathBlockDetector, source, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn math(input: &str) -> Option<(String, bool, String)> {
+        let mut queue = Queue::from_string(input.to_string());
+
+        let result = detect_math(&mut queue)?;
+
+        let display = match result.get_property("attributes") {
+            Value::Dict(attributes) => attributes.get("display") == Value::Boolean(true),
+            _ => return None
+        };
+
+        Some((result.children?[0].content.clone()?.to_string(), display, queue.to_string()))
+    }
+
+    fn math_block(input: &str) -> Option<(String, String)> {
+        let text = Queue::from_string(input.to_string());
+
+        let mut queue = &text[..];
+
+        let result = detect_math_block(&mut queue)?;
+
+        Some((result.children?[0].content.clone()?.to_string(), queue.iter().collect()))
+    }
+
+    #[test]
+    fn test_inline_math() {
+        assert_eq!(math("$x^2$ rest"), Some(("x^2".to_string(), false, " rest".to_string())));
+        assert_eq!(math("$$\\sum_i x_i$$"), Some(("\\sum_i x_i".to_string(), true, "".to_string())));
+        assert_eq!(math("$a \\$ b$"), Some(("a \\$ b".to_string(), false, "".to_string())));
+        assert_eq!(math("$*a* <b>$"), Some(("*a* <b>".to_string(), false, "".to_string())));
+    }
+
+    #[test]
+    fn test_currency() {
+        // Literal dollar signs are raw text
+        assert_eq!(math("$5 and $10"), None);
+        assert_eq!(math("$ x$"), None);
+        assert_eq!(math("$x $"), None);
+        assert_eq!(math("$x$5"), None);
+        assert_eq!(math("$a\n\nb$"), None);
+        assert_eq!(math("$5 and $10, x$"), None);
+
+        let mut queue = Queue::from_string("$5 and $10".to_string());
+
+        assert_eq!(detect_math(&mut queue).unwrap().content, Some(Queue::from_string("$".to_string())));
+    }
+
+    #[test]
+    fn test_math_block() {
+        assert_eq!(math_block("$$\nE = mc^2\n$$\nText"), Some(("E = mc^2".to_string(), "Text".to_string())));
+        assert_eq!(math_block("$$ a\nb $$"), Some(("a\nb".to_string(), "".to_string())));
+        assert_eq!(math_block("$$x$$"), Some(("x".to_string(), "".to_string())));
+
+        assert_eq!(math_block("$$x$$ and text"), None);
+        assert_eq!(math_block("$$\nx\n\n$$"), None);
+        assert_eq!(math_block("$$\nx"), None);
+        assert_eq!(math_block("    $$x$$"), None);
+    }
+}
@@ -0,0 +1,276 @@
+// Markdown Detector Implementations
+// ------------------------
+
+pub mod line;
+pub mod heading;
+pub mod setext_heading;
+pub mod emphasis;
+pub mod code_span;
+pub mod fenced_code;
+pub mod indented_code;
+pub mod blockquote;
+pub mod list;
+pub mod table;
+pub mod link;
+pub mod reference;
+pub mod autolink;
+pub mod thematic_break;
+pub mod line_break;
+pub mod escape;
+pub mod entities;
+pub mod paragraph;
+pub mod soft_break;
+pub mod footnote;
+pub mod inline_extension;
+pub mod front_matter;
+pub mod math;
+pub mod callout;
+pub mod wikilink;
+pub mod include;
+pub mod template;
+
+use crate::base::*;
+use crate::types::{Queue, Token, Dict, Value};
+
+use line::peek_line;
+
+use heading::detect_atx_heading;
+use setext_heading::detect_setext_heading;
+use emphasis::detect_delimiter_run;
+use code_span::detect_code_span;
+use fenced_code::{detect_fenced_code, is_fence};
+use indented_code::detect_indented_code;
+use blockquote::{detect_blockquote, is_blockquote};
+use list::detect_list;
+use table::detect_table;
+use link::{detect_link, detect_image};
+use reference::{LinkReferences, detect_link_definition, detect_reference};
+use autolink::detect_autolink;
+use thematic_break::{detect_thematic_break, is_thematic_break};
+use line_break::detect_line_break;
+use escape::detect_escape;
+use paragraph::detect_paragraph;
+use soft_break::detect_soft_break;
+use footnote::{detect_footnote_definition, detect_footnote_reference};
+use inline_extension::{InlineExtensions, detect_extension_delimiter};
+use math::{detect_math, detect_math_block};
+use callout::{CalloutKinds, detect_callout};
+use wikilink::detect_wikilink;
+use include::detect_include;
+use template::detect_template;
+
+// Base Markdown Detector
+// ------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarkdownDetector {
+    HeadingDetector,
+    SetextHeadingDetector,
+    EmphasisDetector,
+    CodeSpanDetector,
+    FencedCodeDetector,
+    IndentedCodeDetector,
+    BlockquoteDetector,
+    ListDetector,
+    TableDetector,
+    LinkDetector,
+    ImageDetector,
+    LinkDefinitionDetector,
+    ReferenceDetector(LinkReferences),
+    AutolinkDetector,
+    ThematicBreakDetector,
+    LineBreakDetector,
+    EscapeDetector,
+    ParagraphDetector,
+    SoftBreakDetector,
+    FootnoteDefinitionDetector,
+    FootnoteReferenceDetector,
+    InlineExtensionDetector(InlineExtensions),
+    MathDetector,
+    MathBlockDetector,
+    CalloutDetector(CalloutKinds),
+    WikilinkDetector,
+    IncludeDetector,
+    TemplateDetector
+}
+
+impl MarkdownDetector {
+    /// Block detectors only match at the start of a line
+    pub fn is_block(&self) -> bool {
+        match self {
+            MarkdownDetector::HeadingDetector => true,
+            MarkdownDetector::SetextHeadingDetector => true,
+            MarkdownDetector::EmphasisDetector => false,
+            MarkdownDetector::CodeSpanDetector => false,
+            MarkdownDetector::FencedCodeDetector => true,
+            MarkdownDetector::IndentedCodeDetector => true,
+            MarkdownDetector::BlockquoteDetector => true,
+            MarkdownDetector::ListDetector => true,
+            MarkdownDetector::TableDetector => true,
+            MarkdownDetector::LinkDetector => false,
+            MarkdownDetector::ImageDetector => false,
+            MarkdownDetector::LinkDefinitionDetector => true,
+            MarkdownDetector::ReferenceDetector(_) => false,
+            MarkdownDetector::AutolinkDetector => false,
+            MarkdownDetector::ThematicBreakDetector => true,
+            MarkdownDetector::LineBreakDetector => false,
+            MarkdownDetector::EscapeDetector => false,
+            MarkdownDetector::ParagraphDetector => true,
+            MarkdownDetector::SoftBreakDetector => false,
+            MarkdownDetector::FootnoteDefinitionDetector => true,
+            MarkdownDetector::FootnoteReferenceDetector => false,
+            MarkdownDetector::InlineExtensionDetector(_) => false,
+            MarkdownDetector::MathDetector => false,
+            MarkdownDetector::MathBlockDetector => true,
+            MarkdownDetector::CalloutDetector(_) => true,
+            MarkdownDetector::WikilinkDetector => false,
+            MarkdownDetector::IncludeDetector => true,
+            MarkdownDetector::TemplateDetector => false
+        }
+    }
+
+    /// Whether the block can start on the queue directly after a line of paragraph text
+    pub fn can_interrupt_paragraph(&self, queue: &[Token]) -> bool {
+        match self {
+            MarkdownDetector::SetextHeadingDetector => false,
+            MarkdownDetector::IndentedCodeDetector => false,
+            MarkdownDetector::LinkDefinitionDetector => false,
+            MarkdownDetector::FootnoteDefinitionDetector => false,
+            MarkdownDetector::ListDetector => list::can_interrupt_paragraph(&peek_line(queue)),
+            _ => true
+        }
+    }
+
+    /// Whether the content is parsed as blocks again (or only inline otherwise)
+    pub fn has_block_content(&self) -> bool {
+        match self {
+            MarkdownDetector::HeadingDetector => false,
+            MarkdownDetector::SetextHeadingDetector => false,
+            MarkdownDetector::EmphasisDetector => false,
+            MarkdownDetector::CodeSpanDetector => false,
+            MarkdownDetector::FencedCodeDetector => false,
+            MarkdownDetector::IndentedCodeDetector => false,
+            MarkdownDetector::BlockquoteDetector => true,
+            MarkdownDetector::ListDetector => true,
+            MarkdownDetector::TableDetector => false,
+            MarkdownDetector::LinkDetector => false,
+            MarkdownDetector::ImageDetector => false,
+            MarkdownDetector::LinkDefinitionDetector => false,
+            MarkdownDetector::ReferenceDetector(_) => false,
+            MarkdownDetector::AutolinkDetector => false,
+            MarkdownDetector::ThematicBreakDetector => false,
+            MarkdownDetector::LineBreakDetector => false,
+            MarkdownDetector::EscapeDetector => false,
+            MarkdownDetector::ParagraphDetector => false,
+            MarkdownDetector::SoftBreakDetector => false,
+            MarkdownDetector::FootnoteDefinitionDetector => true,
+            MarkdownDetector::FootnoteReferenceDetector => false,
+            MarkdownDetector::InlineExtensionDetector(_) => false,
+            MarkdownDetector::MathDetector => false,
+            MarkdownDetector::MathBlockDetector => false,
+            MarkdownDetector::CalloutDetector(_) => true,
+            MarkdownDetector::WikilinkDetector => false,
+            MarkdownDetector::IncludeDetector => false,
+            MarkdownDetector::TemplateDetector => false
+        }
+    }
+}
+
+impl MarkdownDetector {
+    /// Block phase: detects the block at the front of the text and advances the text past it
+    /// (the text is left in any state if nothing is detected)
+    pub fn detect_block(&self, queue: &mut &[Token]) -> Option<Result> {
+        match self {
+            MarkdownDetector::HeadingDetector => detect_atx_heading(queue),
+            MarkdownDetector::SetextHeadingDetector => detect_setext_heading(queue),
+            MarkdownDetector::FencedCodeDetector => detect_fenced_code(queue),
+            MarkdownDetector::IndentedCodeDetector => detect_indented_code(queue),
+            MarkdownDetector::BlockquoteDetector => detect_blockquote(queue),
+            MarkdownDetector::ListDetector => detect_list(queue),
+            MarkdownDetector::TableDetector => detect_table(queue),
+            MarkdownDetector::LinkDefinitionDetector => detect_link_definition(queue),
+            MarkdownDetector::ThematicBreakDetector => detect_thematic_break(queue),
+            MarkdownDetector::ParagraphDetector => detect_paragraph(queue),
+            MarkdownDetector::FootnoteDefinitionDetector => detect_footnote_definition(queue),
+            MarkdownDetector::MathBlockDetector => detect_math_block(queue),
+            MarkdownDetector::CalloutDetector(kinds) => detect_callout(queue, kinds),
+            MarkdownDetector::IncludeDetector => detect_include(queue),
+            _ => None
+        }
+    }
+}
+
+impl Detectable for MarkdownDetector {
+    fn detect(&self, queue: &mut Queue) -> Option<Result> {
+        if self.is_block() {
+            let mut rest: &[Token] = queue;
+
+            let result = self.detect_block(&mut rest)?;
+
+            let consumed = queue.len() - rest.len();
+
+            queue.drain(0..consumed);
+
+            return Some(result);
+        }
+
+        match self {
+            MarkdownDetector::EmphasisDetector => detect_delimiter_run(queue),
+            MarkdownDetector::CodeSpanDetector => detect_code_span(queue),
+            MarkdownDetector::LinkDetector => detect_link(queue),
+            MarkdownDetector::ImageDetector => detect_image(queue),
+            MarkdownDetector::ReferenceDetector(references) => detect_reference(queue, references),
+            MarkdownDetector::AutolinkDetector => detect_autolink(queue),
+            MarkdownDetector::LineBreakDetector => detect_line_break(queue),
+            MarkdownDetector::EscapeDetector => detect_escape(queue),
+            MarkdownDetector::SoftBreakDetector => detect_soft_break(queue),
+            MarkdownDetector::FootnoteReferenceDetector => detect_footnote_reference(queue),
+            MarkdownDetector::InlineExtensionDetector(extensions) => detect_extension_delimiter(queue, extensions),
+            MarkdownDetector::MathDetector => detect_math(queue),
+            MarkdownDetector::WikilinkDetector => detect_wikilink(queue),
+            MarkdownDetector::TemplateDetector => detect_template(queue),
+            _ => None
+        }
+    }
+}
+
+/// Whether a line starts a block that ends a preceding paragraph
+pub fn interrupts_paragraph(line: &[Token]) -> bool {
+    let mut heading = line;
+
+    detect_atx_heading(&mut heading).is_some() ||
+    is_thematic_break(line) ||
+    is_fence(line) ||
+    is_blockquote(line) ||
+    list::can_interrupt_paragraph(line)
+}
+
+/// Attributes with an empty `class` list, the base of the attributes of every node
+pub fn class_attributes() -> Dict {
+    Dict::from_values(vec![("class".to_string(), Value::Array(vec![]))])
+}
+
+/// Builds a node with its `tag` and `attributes` (further properties are set on the result)
+pub fn node(detector: MarkdownDetector, tag: &str, attributes: Dict, content: Option<Queue>, children: Option<Vec<Result>>) -> Result {
+    let properties = Dict::from_values(
+        vec![
+            ("tag".to_string(), Value::String(tag.to_string())),
+            ("attributes".to_string(), Value::Dict(attributes))
+        ]
+    );
+
+    Result::new(Detector::MarkdownDetector(detector), content, Some(properties), children)
+}
+
+/// A diagnostic of the document with its `kind`, the `label` it is about and a `message`
+pub fn diagnostic(kind: &str, label: &str, message: String) -> Value {
+    Value::Dict(
+        Dict::from_values(
+            vec![
+                ("kind".to_string(), Value::String(kind.to_string())),
+                ("label".to_string(), Value::String(label.to_string())),
+                ("message".to_string(), Value::String(message))
+            ]
+        )
+    )
+}
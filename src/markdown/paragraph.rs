@@ -0,0 +1,113 @@
+// Paragraph Detector
+// ------------------------
+// Groups consecutive non-blank lines of text into `p` nodes:
+//
+//     First line
+//     second line
+//
+//     Next paragraph
+//
+// Paragraphs are not tried at the start of a line like the other blocks, the block phase
+// turns the text between the blocks into paragraphs if this detector is enabled (so every
+// other block can still interrupt a paragraph). The leading whitespace of every line and
+// the trailing whitespace of the paragraph are removed, the line endings are soft breaks.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, node, class_attributes};
+use crate::markdown::line::{take_line, peek_line, is_blank, trim};
+use crate::types::{Queue, Token, Value};
+
+/// Takes the paragraph at the front of the queue (up to the next blank line)
+pub fn detect_paragraph(queue: &mut &[Token]) -> Option<Result> {
+    let mut lines: Vec<Queue> = vec![];
+
+    while !queue.is_empty() && !is_blank(&peek_line(queue)) {
+        let line = take_line(queue);
+
+        let start = line.iter().position(|token| *token != ' ' && *token != '\t').unwrap_or(line.len());
+
+        lines.push(line[start..].to_vec());
+    }
+
+    if lines.is_empty() {
+        return None;
+    }
+
+    let content = trim(&lines.join(&'\n'));
+
+    Some(node(MarkdownDetector::ParagraphDetector, "p", class_attributes(), Some(content), None))
+}
+
+/// Splits text into paragraphs at blank lines, `line` is the line the text starts on
+pub fn paragraphs(text: &[Token], line: i64) -> Vec<Result> {
+    let mut queue = text;
+
+    let mut results = vec![];
+    let mut line = line;
+
+    while !queue.is_empty() {
+        if is_blank(&peek_line(queue)) {
+            take_line(&mut queue);
+
+            line += 1;
+
+            continue;
+        }
+
+        let length = queue.len();
+
+        if let Some(mut result) = detect_paragraph(&mut queue) {
+            result.set_property("line", Value::Integer(line));
+
+            line += text[text.len() - length..text.len() - queue.len()].iter().filter(|token| **token == '\n').count() as i64;
+
+            results.push(result);
+        }
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    #[test]
+    fn test_paragraphs() {
+        let results = paragraphs(&Queue::from_string("\n  one\n two  \n\n\nthree\n".to_string()), 2);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].get_property("tag"), Value::String("p".to_string()));
+        assert_eq!(results[0].content, Some(Queue::from_string("one\ntwo".to_string())));
+        assert_eq!(results[0].get_property("line"), Value::Integer(3));
+        assert_eq!(results[1].content, Some(Queue::from_string("three".to_string())));
+        assert_eq!(results[1].get_property("line"), Value::Integer(7));
+
+        assert!(paragraphs(&Queue::from_string(" \n\t\n".to_string()), 0).is_empty());
+    }
+
+    #[test]
+    fn test_paragraphs_between_blocks() {
+        let mut queue = Queue::from_string("Text\nmore\n# Heading\n\nLast".to_string());
+
+        let results = queue.consume_any(
+            &[
+                Detector::MarkdownDetector(MarkdownDetector::HeadingDetector),
+                Detector::MarkdownDetector(MarkdownDetector::ParagraphDetector),
+                Detector::MarkdownDetector(MarkdownDetector::SoftBreakDetector)
+            ]
+        ).unwrap();
+
+        let tags = results.iter().map(|result| result.get_property("tag")).collect::<Vec<Value>>();
+
+        assert_eq!(tags, vec![Value::String("p".to_string()), Value::String("h1".to_string()), Value::String("p".to_string())]);
+
+        let children = results[0].children.clone().unwrap();
+
+        assert_eq!(children.len(), 3);
+        assert_eq!(children[1].get_property("tag"), Value::String("softbreak".to_string()));
+    }
+}
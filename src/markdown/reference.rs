@@ -0,0 +1,308 @@
+// Reference Link Detectors
+// ------------------------
+// Detects link reference definitions and the links using them:
+//
+//     [full][label], [label][] and [label]
+//     ![image][label]
+//
+//     [label]: https://example.com "Title"
+//
+// Definitions can appear anywhere in the document, so the document is parsed twice:
+// first the definitions are collected from the result tree, then the reference detector
+// resolves the links against them. Labels are matched case-insensitively with
+// whitespace collapsed.
+// ------------------------
+
+use std::collections::HashMap;
+
+use crate::base::*;
+use crate::markdown::MarkdownDetector;
+use crate::markdown::line::{indentation, is_blank};
+use crate::markdown::link::{InlineLink, inline_link, closing_bracket, link_destination, link_title, skip_whitespace, link_result, image_result};
+use crate::types::{Queue, Token, Dict, Value};
+
+pub const MAX_LABEL_LENGTH: usize = 999;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LinkReferences {
+    pub definitions: HashMap<String, (Queue, Option<Queue>)>
+}
+
+impl LinkReferences {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The first definition of a label wins
+    pub fn define(&mut self, label: &[Token], destination: Queue, title: Option<Queue>) {
+        self.definitions.entry(normalize_label(label)).or_insert((destination, title));
+    }
+
+    pub fn get(&self, label: &[Token]) -> Option<&(Queue, Option<Queue>)> {
+        self.definitions.get(&normalize_label(label))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+}
+
+/// Case-folds the label and collapses whitespace
+pub fn normalize_label(label: &[Token]) -> String {
+    label.iter().collect::<String>().split_whitespace().collect::<Vec<&str>>().join(" ").to_lowercase()
+}
+
+/// Parses a link label (`[label]`) at `start`, returns the label and the index after it
+fn link_label(queue: &[Token], start: usize) -> Option<(Queue, usize)> {
+    if queue.get(start) != Some(&'[') {
+        return None;
+    }
+
+    let mut index = start + 1;
+
+    while let Some(token) = queue.get(index) {
+        match token {
+            '\\' => index += 1,
+            '[' => return None,
+            ']' => {
+                let label = queue[start + 1..index].to_vec();
+
+                if is_blank(&label) || label.len() > MAX_LABEL_LENGTH {
+                    return None;
+                }
+
+                return Some((label, index + 1));
+            },
+            _ => {}
+        }
+
+        index += 1;
+    }
+
+    None
+}
+
+// Definitions
+// ------------------------
+
+/// Index after the trailing whitespace if only whitespace follows until the end of the line
+fn line_end(queue: &[Token], start: usize) -> Option<usize> {
+    let mut index = start;
+
+    while let Some(token) = queue.get(index) {
+        match token {
+            ' ' | '\t' => index += 1,
+            '\n' => return Some(index + 1),
+            _ => return None
+        }
+    }
+
+    Some(index)
+}
+
+pub fn detect_link_definition(queue: &mut &[Token]) -> Option<Result> {
+    if indentation(queue) > 3 {
+        return None;
+    }
+
+    let start = queue.iter().position(|token| *token != ' ')?;
+
+    let (label, after) = link_label(queue, start)?;
+
+    if queue.get(after) != Some(&':') {
+        return None;
+    }
+
+    let index = skip_whitespace(queue, after + 1);
+
+    let (destination, after) = link_destination(queue, index)?;
+
+    // Only a destination wrapped in `<>` may be empty
+    if after == index {
+        return None;
+    }
+
+    let destination_end = line_end(queue, after);
+
+    let title_start = skip_whitespace(queue, after);
+
+    // The title has to be separated by whitespace and has to end the line
+    let (title, end) = match link_title(queue, title_start) {
+        Some((title, title_end)) if title_start > after => match line_end(queue, title_end) {
+            Some(end) => (Some(title), end),
+            None => (None, destination_end?)
+        },
+        _ => (None, destination_end?)
+    };
+
+    *queue = &queue[end..];
+
+    let properties = Dict::from_values(
+        vec![
+            ("label".to_string(), Value::String(label.iter().collect())),
+            ("destination".to_string(), Value::String(destination.iter().collect())),
+            ("title".to_string(), title.map(|title| Value::String(title.iter().collect())).unwrap_or(Value::NoneValue))
+        ]
+    );
+
+    Some(
+        Result::new(
+            Detector::MarkdownDetector(MarkdownDetector::LinkDefinitionDetector),
+            None,
+            Some(properties),
+            None
+        )
+    )
+}
+
+/// Removes all definitions from the result tree and collects them
+pub fn collect_definitions(results: &mut Vec<Result>, references: &mut LinkReferences) {
+    results.retain(|result| {
+        if result.detector != Detector::MarkdownDetector(MarkdownDetector::LinkDefinitionDetector) {
+            return true;
+        }
+
+        let text = |key: &str| result.get_property(key).to_str().map(|text| text.chars().collect::<Queue>());
+
+        if let (Some(label), Some(destination)) = (text("label"), text("destination")) {
+            references.define(&label, destination, text("title"));
+        }
+
+        false
+    });
+
+    for result in results.iter_mut() {
+        if let Some(children) = &mut result.children {
+            collect_definitions(children, references);
+        }
+    }
+}
+
+// References
+// ------------------------
+
+/// Parses a full, collapsed or shortcut reference at the front of the queue (without the `!` of an image)
+fn reference_link(queue: &[Token], references: &LinkReferences) -> Option<InlineLink> {
+    let end = closing_bracket(queue, 0)?;
+
+    let text = queue[1..end].to_vec();
+
+    let (label, length) = match link_label(queue, end + 1) {
+        // Full reference
+        Some((label, after)) => (label, after),
+        // Collapsed reference
+        None if queue.get(end + 1) == Some(&'[') && queue.get(end + 2) == Some(&']') => (text.clone(), end + 3),
+        // Shortcut reference
+        None => (text.clone(), end + 1)
+    };
+
+    let (destination, title) = references.get(&label)?.clone();
+
+    Some(
+        InlineLink {
+            text,
+            destination,
+            title,
+            length
+        }
+    )
+}
+
+/// Links can't contain other (inline or reference) links
+fn contains_link(text: &[Token], references: &LinkReferences) -> bool {
+    (0..text.len()).any(|index| {
+        text[index] == '[' &&
+        (index == 0 || (text[index - 1] != '\\' && text[index - 1] != '!')) &&
+        (inline_link(&text[index..]).is_some() || reference_link(&text[index..], references).is_some())
+    })
+}
+
+pub fn detect_reference(queue: &mut Queue, references: &LinkReferences) -> Option<Result> {
+    let image = queue.first() == Some(&'!');
+
+    let start = image as usize;
+
+    if queue.get(start) != Some(&'[') || references.is_empty() {
+        return None;
+    }
+
+    let link = reference_link(&queue[start..], references)?;
+
+    if !image && contains_link(&link.text, references) {
+        return None;
+    }
+
+    queue.drain(0..start + link.length);
+
+    Some(
+        if image {
+            image_result(MarkdownDetector::ImageDetector, link.text, link.destination, link.title)
+        } else {
+            link_result(MarkdownDetector::LinkDetector, link.text, link.destination, link.title)
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn references() -> LinkReferences {
+        let mut queue = Queue::from_string("[Foo  Bar]: /url \"Title\"\n[baz]: <my url>\n[foo bar]: /ignored\n".to_string());
+
+        let mut results = queue.consume_any(&[Detector::MarkdownDetector(MarkdownDetector::LinkDefinitionDetector)]).unwrap();
+
+        let mut references = LinkReferences::new();
+
+        collect_definitions(&mut results, &mut references);
+
+        assert!(results.is_empty());
+
+        references
+    }
+
+    fn href(input: &str) -> Option<(String, Value)> {
+        let mut queue = Queue::from_string(input.to_string());
+
+        let result = detect_reference(&mut queue, &references())?;
+
+        let href = match result.get_property("attributes") {
+            Value::Dict(attributes) => attributes.get(if result.get_property("tag") == Value::String("img".to_string()) { "src" } else { "href" }),
+            _ => Value::NoneValue
+        };
+
+        Some((queue.to_string(), href))
+    }
+
+    #[test]
+    fn test_link_definition() {
+        let references = references();
+
+        assert_eq!(references.definitions.len(), 2);
+        assert_eq!(references.get(&Queue::from_string("FOO bar".to_string())), Some(&(Queue::from_string("/url".to_string()), Some(Queue::from_string("Title".to_string())))));
+        assert_eq!(references.get(&Queue::from_string("baz".to_string())), Some(&(Queue::from_string("my url".to_string()), None)));
+
+        assert!(detect_link_definition(&mut &Queue::from_string("[a]: /url \"title\" text\n".to_string())[..]).is_none());
+
+        let text = Queue::from_string("[a]: /url\n\"title\" text\n".to_string());
+
+        let mut queue = &text[..];
+
+        assert!(detect_link_definition(&mut queue).is_some());
+        assert_eq!(queue.to_vec(), Queue::from_string("\"title\" text\n".to_string()));
+    }
+
+    #[test]
+    fn test_reference_link() {
+        assert_eq!(href("[text][foo bar] rest"), Some((" rest".to_string(), Value::String("/url".to_string()))));
+        assert_eq!(href("[Baz][]"), Some(("".to_string(), Value::String("my url".to_string()))));
+        assert_eq!(href("[baz]"), Some(("".to_string(), Value::String("my url".to_string()))));
+        assert_eq!(href("![baz]"), Some(("".to_string(), Value::String("my url".to_string()))));
+
+        assert_eq!(href("[text][undefined]"), None);
+        assert_eq!(href("[baz][undefined]"), None);
+        assert_eq!(href("[undefined]"), None);
+    }
+}
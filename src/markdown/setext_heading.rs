@@ -0,0 +1,102 @@
+// Setext Heading Detector
+// ------------------------
+// Detects paragraph text followed by an underline of `=` (h1) or `-` (h2):
+//
+//     Heading
+//     =======
+//
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, interrupts_paragraph};
+use crate::markdown::heading::heading_result;
+use crate::markdown::line::{take_line, is_blank, indentation, trim};
+use crate::types::{Queue, Token};
+
+/// Returns the heading level if the line is a setext underline
+fn underline_level(line: &[Token]) -> Option<usize> {
+    if indentation(line) > 3 {
+        return None;
+    }
+
+    let underline = trim(line);
+
+    match underline.first() {
+        Some('=') if underline.iter().all(|token| *token == '=') => Some(1),
+        Some('-') if underline.iter().all(|token| *token == '-') => Some(2),
+        _ => None
+    }
+}
+
+pub fn detect_setext_heading(queue: &mut &[Token]) -> Option<Result> {
+    let mut lines: Vec<Queue> = vec![];
+
+    loop {
+        if queue.is_empty() {
+            return None;
+        }
+
+        let line = take_line(queue);
+
+        if is_blank(&line) {
+            return None;
+        }
+
+        if lines.is_empty() {
+            // The paragraph itself may not start with another block or an indented line
+            if indentation(&line) > 3 || interrupts_paragraph(&line) {
+                return None;
+            }
+        } else {
+            if let Some(level) = underline_level(&line) {
+                let content = lines.join(&'\n');
+
+                return Some(
+                    heading_result(MarkdownDetector::SetextHeadingDetector, level, content)
+                );
+            }
+
+            if interrupts_paragraph(&line) {
+                return None;
+            }
+        }
+
+        lines.push(trim(&line));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn heading(input: &str) -> Option<Result> {
+        let mut queue = Queue::from_string(input.to_string());
+
+        let (_, _, result) = queue.consume(&Detector::MarkdownDetector(MarkdownDetector::SetextHeadingDetector));
+
+        result
+    }
+
+    #[test]
+    fn test_setext_heading_detector() {
+        let result = heading("Title\n=====\nText").unwrap();
+
+        assert_eq!(result.get_property("tag"), Value::String("h1".to_string()));
+        assert_eq!(result.content, Some(Queue::from_string("Title".to_string())));
+
+        let result = heading("Multi\n  line\n---").unwrap();
+
+        assert_eq!(result.get_property("tag"), Value::String("h2".to_string()));
+        assert_eq!(result.content, Some(Queue::from_string("Multi\nline".to_string())));
+    }
+
+    #[test]
+    fn test_setext_heading_detector_incorrect() {
+        assert_eq!(heading("Title"), None);
+        assert_eq!(heading("Title\n\n==="), None);
+        assert_eq!(heading("Title\n= ="), None);
+        assert_eq!(heading("Text\n# Heading\n---"), None);
+    }
+}
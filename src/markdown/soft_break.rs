@@ -0,0 +1,46 @@
+// Soft Break Detector
+// ------------------------
+// Detects the line endings inside of inline content (that are not hard line breaks):
+//
+//     First line
+//     second line
+//
+// The line ending and the whitespace around it are replaced by a `softbreak` node,
+// which is rendered as a newline or a space.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, node, class_attributes};
+use crate::types::Queue;
+
+pub fn detect_soft_break(queue: &mut Queue) -> Option<Result> {
+    let spaces = queue.iter().take_while(|token| **token == ' ').count();
+
+    if queue.get(spaces) != Some(&'\n') {
+        return None;
+    }
+
+    // Leading whitespace of the next line is removed as well
+    let indent = queue[spaces + 1..].iter().take_while(|token| **token == ' ' || **token == '\t').count();
+
+    queue.drain(0..spaces + 1 + indent);
+
+    Some(node(MarkdownDetector::SoftBreakDetector, "softbreak", class_attributes(), None, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::Queue;
+
+    use super::*;
+
+    #[test]
+    fn test_soft_break() {
+        let mut queue = Queue::from_string(" \n  next".to_string());
+
+        assert!(detect_soft_break(&mut queue).is_some());
+        assert_eq!(queue, Queue::from_string("next".to_string()));
+
+        assert!(detect_soft_break(&mut Queue::from_string("text\n".to_string())).is_none());
+    }
+}
@@ -0,0 +1,230 @@
+// Table Detector
+// ------------------------
+// Detects GFM pipe tables with a header row, a delimiter row and optional body rows:
+//
+//     | Left | Center | Right |
+//     |:-----|:------:|------:|
+//     | a    | `b \| c` | d   |
+//
+// The alignment of a column is set as the `align` attribute of its `th`/`td` cells.
+// Escaped pipes (`\|`) do not split cells, the content of a cell is parsed inline.
+// The table ends at a blank line or the start of another block.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, interrupts_paragraph, node, class_attributes};
+use crate::markdown::line::{take_line, peek_line, is_blank, indentation, trim};
+use crate::types::{Queue, Token, Value};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Alignment {
+    None,
+    Left,
+    Center,
+    Right
+}
+
+/// Splits a row into its trimmed cells, leading and trailing pipes are optional
+pub fn split_row(line: &[Token]) -> Vec<Queue> {
+    let line = trim(line);
+
+    let mut cells: Vec<Queue> = vec![];
+    let mut cell: Queue = vec![];
+
+    let mut index = 0;
+
+    while index < line.len() {
+        match line[index] {
+            '\\' if line.get(index + 1) == Some(&'|') => {
+                cell.push('|');
+
+                index += 1;
+            },
+            '|' => {
+                // A leading pipe does not start an empty cell
+                if index > 0 {
+                    cells.push(trim(&cell));
+                }
+
+                cell = vec![];
+            },
+            token => cell.push(token)
+        }
+
+        index += 1;
+    }
+
+    // A trailing pipe does not end with an empty cell
+    if line.last() != Some(&'|') || line.ends_with(&['\\', '|']) {
+        cells.push(trim(&cell));
+    }
+
+    cells
+}
+
+/// Whether the line contains a pipe that is not escaped
+fn has_pipe(line: &[Token]) -> bool {
+    line.iter().enumerate().any(|(index, token)| *token == '|' && (index == 0 || line[index - 1] != '\\'))
+}
+
+/// Parses the delimiter row (`| --- | :-: |`) into the alignments of the columns
+pub fn delimiter_row(line: &[Token]) -> Option<Vec<Alignment>> {
+    if indentation(line) > 3 {
+        return None;
+    }
+
+    split_row(line).iter().map(|cell| {
+        let left = cell.first() == Some(&':');
+        let right = cell.len() > 1 && cell.last() == Some(&':');
+
+        let dashes = &cell[left as usize..cell.len() - right as usize];
+
+        if dashes.is_empty() || dashes.iter().any(|token| *token != '-') {
+            return None;
+        }
+
+        Some(
+            match (left, right) {
+                (true, true) => Alignment::Center,
+                (true, false) => Alignment::Left,
+                (false, true) => Alignment::Right,
+                (false, false) => Alignment::None
+            }
+        )
+    }).collect()
+}
+
+/// Builds a row with exactly one cell per column (missing cells are empty, excess cells are dropped)
+fn row(cells: &[Queue], alignments: &[Alignment], tag: &str) -> Result {
+    let children = alignments.iter().enumerate().map(|(index, alignment)| {
+        let mut attributes = class_attributes();
+
+        match alignment {
+            Alignment::Left => attributes.set("align", Value::String("left".to_string())),
+            Alignment::Center => attributes.set("align", Value::String("center".to_string())),
+            Alignment::Right => attributes.set("align", Value::String("right".to_string())),
+            Alignment::None => {}
+        }
+
+        node(MarkdownDetector::TableDetector, tag, attributes, Some(cells.get(index).cloned().unwrap_or_default()), None)
+    }).collect::<Vec<Result>>();
+
+    node(MarkdownDetector::TableDetector, "tr", class_attributes(), None, Some(children))
+}
+
+pub fn detect_table(queue: &mut &[Token]) -> Option<Result> {
+    let mut rest = *queue;
+
+    let header = take_line(&mut rest);
+
+    if indentation(&header) > 3 || !has_pipe(&header) {
+        return None;
+    }
+
+    let alignments = delimiter_row(&take_line(&mut rest))?;
+
+    let header_cells = split_row(&header);
+
+    // The header has to have as many cells as the delimiter row
+    if header_cells.len() != alignments.len() {
+        return None;
+    }
+
+    let mut rows: Vec<Result> = vec![];
+
+    while !rest.is_empty() {
+        let line = peek_line(rest);
+
+        if is_blank(&line) || interrupts_paragraph(&line) {
+            break;
+        }
+
+        take_line(&mut rest);
+
+        rows.push(row(&split_row(&line), &alignments, "td"));
+    }
+
+    let consumed = queue.len() - rest.len();
+
+    *queue = &queue[consumed..];
+
+    let mut children = vec![
+        node(MarkdownDetector::TableDetector, "thead", class_attributes(), None, Some(vec![row(&header_cells, &alignments, "th")]))
+    ];
+
+    if !rows.is_empty() {
+        children.push(node(MarkdownDetector::TableDetector, "tbody", class_attributes(), None, Some(rows)));
+    }
+
+    Some(node(MarkdownDetector::TableDetector, "table", class_attributes(), None, Some(children)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn cells(input: &str) -> Vec<String> {
+        split_row(&Queue::from_string(input.to_string())).iter().map(|cell| cell.to_string()).collect()
+    }
+
+    fn tags(results: &[Result]) -> Vec<Value> {
+        results.iter().map(|result| result.get_property("tag")).collect()
+    }
+
+    #[test]
+    fn test_split_row() {
+        assert_eq!(cells("| a | b |"), vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(cells("a|b"), vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(cells("| a \\| b | |"), vec!["a | b".to_string(), "".to_string()]);
+        assert_eq!(cells("| a | b \\|"), vec!["a".to_string(), "b |".to_string()]);
+    }
+
+    #[test]
+    fn test_delimiter_row() {
+        let alignments = delimiter_row(&Queue::from_string("|:--| :-: | --:|---|".to_string())).unwrap();
+
+        assert_eq!(alignments, vec![Alignment::Left, Alignment::Center, Alignment::Right, Alignment::None]);
+
+        assert!(delimiter_row(&Queue::from_string("| -- | a |".to_string())).is_none());
+        assert!(delimiter_row(&Queue::from_string("| : |".to_string())).is_none());
+    }
+
+    #[test]
+    fn test_table_detector() {
+        let text = Queue::from_string("| a | b |\n|:-|-:|\n| 1 | 2 | 3 |\n| 4 |\n\nText".to_string());
+
+        let mut queue = &text[..];
+
+        let result = detect_table(&mut queue).unwrap();
+
+        assert_eq!(queue.to_vec(), Queue::from_string("\nText".to_string()));
+        assert_eq!(result.get_property("tag"), Value::String("table".to_string()));
+
+        let sections = result.children.clone().unwrap();
+
+        assert_eq!(tags(&sections), vec![Value::String("thead".to_string()), Value::String("tbody".to_string())]);
+
+        let rows = sections[1].children.clone().unwrap();
+
+        assert_eq!(rows.len(), 2);
+
+        let cells = rows[1].children.clone().unwrap();
+
+        assert_eq!(cells.len(), 2);
+        assert_eq!(cells[0].content, Some(Queue::from_string("4".to_string())));
+        assert_eq!(cells[1].content, Some(vec![]));
+
+        match cells[1].get_property("attributes") {
+            Value::Dict(attributes) => assert_eq!(attributes.get("align"), Value::String("right".to_string())),
+            _ => panic!("Cells should have attributes")
+        }
+    }
+
+    #[test]
+    fn test_table_mismatch() {
+        assert!(detect_table(&mut &Queue::from_string("| a | b |\n| --- |\n".to_string())[..]).is_none());
+        assert!(detect_table(&mut &Queue::from_string("no pipe\n| --- |\n".to_string())[..]).is_none());
+    }
+}
@@ -0,0 +1,461 @@
+// Template Detector
+// ------------------------
+// Detects template invocations with positional and named arguments:
+//
+//     {{Infobox|Physics|name=Optics|year="2020"}}
+//
+// The results are `template` nodes with the `name` and the `arguments` as attributes.
+// Arguments are separated by `|` (not inside nested templates or wiki links), named
+// arguments are parsed like HTML attributes (quoted or not), positional arguments are
+// numbered from `1`. Names starting with `include:` are include directives, not templates.
+//
+// `expand_templates` substitutes the invocations of registered templates in the source
+// before it is parsed: `{{{name}}}` (or `{{{name|default}}}`) in the body of a template is
+// replaced with the argument (or removed without one). Templates used in the expansion are expanded again. Code blocks
+// and code spans are kept as they are.
+// ------------------------
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::base::*;
+use crate::detectors::property_detector::PropertyDetector;
+use crate::detectors::word_detector::VALID_IDENTIFIER_TOKENS;
+use crate::markdown::{MarkdownDetector, node, class_attributes};
+use crate::markdown::code_span::detect_code_span;
+use crate::markdown::fenced_code::{detect_fenced_code, is_fence};
+use crate::markdown::include::INCLUDE_PREFIX;
+use crate::markdown::indented_code::{detect_indented_code, CODE_INDENTATION};
+use crate::markdown::line::{trim, is_blank, indentation};
+use crate::types::{Queue, Token, Dict, Value};
+
+pub const MAX_EXPANSION_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateCall {
+    pub name: String,
+    pub arguments: Vec<(String, String)>, // Positional arguments have their number as key
+    pub length: usize // Number of tokens the invocation takes
+}
+
+fn starts_with(text: &[Token], prefix: &str) -> bool {
+    text.iter().zip(prefix.chars()).filter(|(a, b)| *a == b).count() == prefix.len()
+}
+
+/// Parses a named argument (`key = value` or `key = "value"`)
+fn named_argument(argument: &[Token]) -> Option<(String, String)> {
+    let mut queue = argument.to_vec();
+
+    let (matched, _, result) = queue.consume(&Detector::PropertyDetector(PropertyDetector::new(None, Some(true))));
+
+    if matched && is_blank(&queue) {
+        let properties = result?.properties?;
+
+        return Some((properties.get("key").to_str()?, properties.get("value").to_str()?));
+    }
+
+    let equals = argument.iter().position(|token| *token == '=')?;
+
+    let key = trim(&argument[..equals]);
+
+    if key.is_empty() || !key.iter().all(|token| VALID_IDENTIFIER_TOKENS.contains(token)) {
+        return None;
+    }
+
+    Some((key.iter().collect(), trim(&argument[equals + 1..]).iter().collect()))
+}
+
+/// Parses a template invocation at the front of the text
+pub fn template_call(text: &[Token]) -> Option<TemplateCall> {
+    if !starts_with(text, "{{") || starts_with(text, "{{{") {
+        return None;
+    }
+
+    let mut parts: Vec<Queue> = vec![vec![]];
+
+    let mut braces = 0;
+    let mut brackets = 0;
+
+    let mut index = 2;
+
+    loop {
+        let rest = &text[index..];
+
+        let (part, length) = if starts_with(rest, "}}") && braces == 0 {
+            break;
+        } else if starts_with(rest, "{{") {
+            braces += 1;
+
+            ("{{", 2)
+        } else if starts_with(rest, "}}") {
+            braces -= 1;
+
+            ("}}", 2)
+        } else if starts_with(rest, "[[") {
+            brackets += 1;
+
+            ("[[", 2)
+        } else if starts_with(rest, "]]") && brackets > 0 {
+            brackets -= 1;
+
+            ("]]", 2)
+        } else {
+            match rest.first()? {
+                '|' if braces == 0 && brackets == 0 => {
+                    parts.push(vec![]);
+
+                    index += 1;
+
+                    continue;
+                },
+                _ => ("", 1)
+            }
+        };
+
+        let last = parts.len() - 1;
+
+        match part {
+            "" => parts[last].push(text[index]),
+            part => parts[last].extend(part.chars())
+        }
+
+        index += length;
+    }
+
+    let name = trim(&parts[0]);
+
+    if name.is_empty() || name.iter().any(|token| ['\n', '{', '}', '[', ']'].contains(token)) {
+        return None;
+    }
+
+    if starts_with(&name, INCLUDE_PREFIX) {
+        return None;
+    }
+
+    let mut arguments: Vec<(String, String)> = vec![];
+
+    let mut position = 0;
+
+    for argument in &parts[1..] {
+        match named_argument(argument) {
+            Some(named) => arguments.push(named),
+            None => {
+                position += 1;
+
+                arguments.push((position.to_string(), trim(argument).iter().collect()));
+            }
+        }
+    }
+
+    Some(
+        TemplateCall {
+            name: name.iter().collect(),
+            arguments,
+            length: index + 2
+        }
+    )
+}
+
+pub fn detect_template(queue: &mut Queue) -> Option<Result> {
+    let call = template_call(queue)?;
+
+    queue.drain(0..call.length);
+
+    let mut arguments = Dict::new();
+
+    for (key, value) in call.arguments {
+        arguments.set(&key, Value::String(value));
+    }
+
+    let mut attributes = class_attributes();
+
+    attributes.set("name", Value::String(call.name));
+    attributes.set("arguments", Value::Dict(arguments));
+
+    Some(node(MarkdownDetector::TemplateDetector, "template", attributes, None, None))
+}
+
+// Expansion
+// ------------------------
+
+/// The bodies of the registered templates by name
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TemplateRegistry {
+    pub templates: HashMap<String, String>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateError {
+    /// The chain of templates ending with the template expanded again
+    Cycle(Vec<String>),
+    /// The chain of templates exceeding the maximum depth
+    TooDeep(Vec<String>)
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TemplateError::Cycle(chain) => write!(f, "Template cycle: {}", chain.join(" -> ")),
+            TemplateError::TooDeep(chain) => write!(f, "Templates are nested too deeply: {}", chain.join(" -> "))
+        }
+    }
+}
+
+/// Replaces the `{{{name}}}` and `{{{name|default}}}` parameters in the body of a template
+fn substitute(body: &[Token], arguments: &[(String, String)]) -> Queue {
+    let mut substituted: Queue = vec![];
+
+    let mut index = 0;
+
+    while index < body.len() {
+        let end = if starts_with(&body[index..], "{{{") {
+            body[index + 3..].windows(3).position(|window| window == ['}', '}', '}']).map(|end| index + 3 + end)
+        } else {
+            None
+        };
+
+        let end = match end {
+            Some(end) => end,
+            None => {
+                substituted.push(body[index]);
+
+                index += 1;
+
+                continue;
+            }
+        };
+
+        let parameter = &body[index + 3..end];
+
+        let (name, default) = match parameter.iter().position(|token| *token == '|') {
+            Some(separator) => (trim(&parameter[..separator]), Some(&parameter[separator + 1..])),
+            None => (trim(parameter), None)
+        };
+
+        let name = name.iter().collect::<String>();
+
+        match (arguments.iter().find(|(key, _)| *key == name), default) {
+            (Some((_, value)), _) => substituted.extend(value.chars()),
+            (None, Some(default)) => substituted.extend(default),
+            // Parameters without an argument are removed (`{{{name}}}` would be parsed as a template)
+            (None, None) => {}
+        }
+
+        index = end + 3;
+    }
+
+    substituted
+}
+
+/// Number of tokens of the code block or code span at the index (kept verbatim in the expansion)
+fn code_length(text: &[Token], index: usize) -> Option<usize> {
+    let rest = &text[index..];
+
+    let detect = |detector: fn(&mut &[Token]) -> Option<Result>| {
+        let mut queue = rest;
+
+        detector(&mut queue).map(|_| rest.len() - queue.len())
+    };
+
+    if index == 0 || text[index - 1] == '\n' {
+        let line = &rest[..rest.iter().position(|token| *token == '\n').unwrap_or(rest.len())];
+
+        if is_fence(line) {
+            return detect(detect_fenced_code);
+        }
+
+        let previous = &text[..index.saturating_sub(1)];
+        let previous = &previous[previous.iter().rposition(|token| *token == '\n').map_or(0, |end| end + 1)..];
+
+        // Indented code can't interrupt a paragraph
+        if indentation(line) >= CODE_INDENTATION && is_blank(previous) {
+            if let Some(length) = detect(detect_indented_code) {
+                return Some(length);
+            }
+        }
+    }
+
+    if rest.first() == Some(&'`') {
+        let mut queue = rest.to_vec();
+
+        return detect_code_span(&mut queue).map(|_| rest.len() - queue.len());
+    }
+
+    None
+}
+
+fn expand(text: &[Token], registry: &TemplateRegistry, chain: &mut Vec<String>) -> std::result::Result<Queue, TemplateError> {
+    let mut expanded: Queue = vec![];
+
+    let mut index = 0;
+
+    while index < text.len() {
+        // Escaped characters can't start code
+        let length = if text[index] == '\\' { Some(2) } else { code_length(text, index) };
+
+        if let Some(length) = length {
+            let end = (index + length).min(text.len());
+
+            expanded.extend(&text[index..end]);
+
+            index = end;
+
+            continue;
+        }
+
+        let call = template_call(&text[index..]);
+
+        let (call, body) = match call.and_then(|call| registry.templates.get(&call.name).map(|body| (call, body))) {
+            Some(template) => template,
+            None => {
+                expanded.push(text[index]);
+
+                index += 1;
+
+                continue;
+            }
+        };
+
+        let cycle = chain.contains(&call.name);
+        let too_deep = chain.len() >= MAX_EXPANSION_DEPTH;
+
+        chain.push(call.name.clone());
+
+        if cycle {
+            return Err(TemplateError::Cycle(chain.clone()));
+        }
+
+        if too_deep {
+            return Err(TemplateError::TooDeep(chain.clone()));
+        }
+
+        let body = substitute(&body.chars().collect::<Queue>(), &call.arguments);
+
+        expanded.extend(expand(&body, registry, chain)?);
+
+        chain.pop();
+
+        index += call.length;
+    }
+
+    Ok(expanded)
+}
+
+/// Expands the invocations of the registered templates in the source (unknown templates are kept)
+pub fn expand_templates(input: &str, registry: &TemplateRegistry) -> std::result::Result<String, TemplateError> {
+    let input = input.chars().collect::<Queue>();
+
+    Ok(expand(&input, registry, &mut vec![])?.iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn call(input: &str) -> Option<TemplateCall> {
+        template_call(&Queue::from_string(input.to_string()))
+    }
+
+    fn arguments(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+    }
+
+    fn registry() -> TemplateRegistry {
+        TemplateRegistry {
+            templates: HashMap::from(
+                [
+                    ("Infobox".to_string(), "<div class=\"infobox\">{{{name}}} ({{{year|unknown}}}) {{Tag|{{{1}}}}}</div>".to_string()),
+                    ("Tag".to_string(), "`{{{1}}}`".to_string()),
+                    ("Loop".to_string(), "{{Loop}}".to_string()),
+                    ("Path".to_string(), "a/{{{q}}}/b".to_string())
+                ]
+            )
+        }
+    }
+
+    #[test]
+    fn test_template_call() {
+        assert_eq!(
+            call("{{Infobox | Physics |name = Optics|year=\"2020\"}} rest"),
+            Some(
+                TemplateCall {
+                    name: "Infobox".to_string(),
+                    arguments: arguments(&[("1", "Physics"), ("name", "Optics"), ("year", "2020")]),
+                    length: 48
+                }
+            )
+        );
+
+        assert_eq!(
+            call("{{Cite|[[Page|label]]|{{Inner|a}}|a = b = c}}").map(|call| call.arguments),
+            Some(arguments(&[("1", "[[Page|label]]"), ("2", "{{Inner|a}}"), ("a", "b = c")]))
+        );
+
+        assert_eq!(call("{{Date}}").map(|call| call.arguments), Some(vec![]));
+
+        assert_eq!(call("{{{param}}}"), None);
+        assert_eq!(call("{{Unclosed|a"), None);
+        assert_eq!(call("{{|a}}"), None);
+        assert_eq!(call("{{include:Page}}"), None);
+    }
+
+    #[test]
+    fn test_template_detector() {
+        let mut queue = Queue::from_string("{{Tag|a|b=c}}".to_string());
+
+        let result = detect_template(&mut queue).unwrap();
+
+        assert!(queue.is_empty());
+
+        match result.get_property("attributes") {
+            Value::Dict(attributes) => {
+                assert_eq!(attributes.get("name"), Value::String("Tag".to_string()));
+                assert_eq!(attributes.get("arguments"), Value::Dict(Dict::from_values(vec![
+                    ("1".to_string(), Value::String("a".to_string())),
+                    ("b".to_string(), Value::String("c".to_string()))
+                ])));
+            },
+            _ => panic!("Templates should have attributes")
+        }
+    }
+
+    #[test]
+    fn test_expand_templates() {
+        assert_eq!(
+            expand_templates("A {{Infobox|math|name=Optics}} {{Unknown|x}}", &registry()),
+            Ok("A <div class=\"infobox\">Optics (unknown) `math`</div> {{Unknown|x}}".to_string())
+        );
+
+        assert_eq!(expand_templates("{{Tag}}", &registry()), Ok("``".to_string()));
+
+        // The expansion of a template without the arguments doesn't contain templates
+        let expanded = expand_templates("{{Path}}", &registry()).unwrap();
+
+        let detectors = vec![
+            Detector::MarkdownDetector(MarkdownDetector::ParagraphDetector),
+            Detector::MarkdownDetector(MarkdownDetector::TemplateDetector)
+        ];
+
+        let results = parse_inlines(Queue::from_string(expanded).consume_blocks(&detectors).unwrap(), &detectors);
+
+        let children = results[0].children.clone().unwrap();
+
+        assert!(children.iter().all(|child| child.detector == Detector::RawDetector));
+        assert_eq!(children.iter().map(|child| child.content.clone().unwrap().to_string()).collect::<String>(), "a//b".to_string());
+
+        // Code is kept as it is
+        assert_eq!(
+            expand_templates("```\n{{Tag|a}}\n```\n`{{Tag|b}}` {{Tag|c}}\n\n    {{Tag|d}}", &registry()),
+            Ok("```\n{{Tag|a}}\n```\n`{{Tag|b}}` `c`\n\n    {{Tag|d}}".to_string())
+        );
+
+        assert_eq!(expand_templates("Text\n    {{Tag|a}}", &registry()), Ok("Text\n    `a`".to_string()));
+
+        assert_eq!(
+            expand_templates("{{Loop}}", &registry()),
+            Err(TemplateError::Cycle(vec!["Loop".to_string(), "Loop".to_string()]))
+        );
+    }
+}
@@ -0,0 +1,79 @@
+// Thematic Break Detector
+// ------------------------
+// Detects a line of three or more `-`, `*` or `_` (optionally separated by spaces or tabs):
+//
+//     ***
+//     - - -
+//
+// A thematic break takes precedence over a list item and produces an `hr` node.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, node, class_attributes};
+use crate::markdown::line::{take_line, indentation};
+use crate::types::Token;
+
+pub const MIN_BREAK_LENGTH: usize = 3;
+
+/// Whether the line is a thematic break
+pub fn is_thematic_break(line: &[Token]) -> bool {
+    if indentation(line) > 3 {
+        return false;
+    }
+
+    let tokens = line.iter().filter(|token| **token != ' ' && **token != '\t').collect::<Vec<&Token>>();
+
+    match tokens.first() {
+        Some(first) if ['-', '*', '_'].contains(*first) => {
+            tokens.len() >= MIN_BREAK_LENGTH && tokens.iter().all(|token| token == first)
+        },
+        _ => false
+    }
+}
+
+pub fn detect_thematic_break(queue: &mut &[Token]) -> Option<Result> {
+    if !is_thematic_break(&take_line(queue)) {
+        return None;
+    }
+
+    Some(node(MarkdownDetector::ThematicBreakDetector, "hr", class_attributes(), None, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn is_break(input: &str) -> bool {
+        is_thematic_break(&Queue::from_string(input.to_string()))
+    }
+
+    #[test]
+    fn test_thematic_break() {
+        assert!(is_break("***"));
+        assert!(is_break(" - - -"));
+        assert!(is_break("_____\t"));
+
+        assert!(!is_break("**"));
+        assert!(!is_break("*-*"));
+        assert!(!is_break("    ---"));
+        assert!(!is_break("--- a"));
+    }
+
+    #[test]
+    fn test_thematic_break_detector() {
+        let mut queue = Queue::from_string("Text\n* * *\n- item".to_string());
+
+        let results = queue.consume_any(
+            &[
+                Detector::MarkdownDetector(MarkdownDetector::ThematicBreakDetector),
+                Detector::MarkdownDetector(MarkdownDetector::ListDetector)
+            ]
+        ).unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[1].get_property("tag"), Value::String("hr".to_string()));
+        assert_eq!(results[2].get_property("tag"), Value::String("ul".to_string()));
+    }
+}
@@ -0,0 +1,158 @@
+// Wikilink Detector
+// ------------------------
+// Detects internal wiki links:
+//
+//     [[Page Name]], [[Page Name#Section]], [[Page Name|label]] or [[#Section|label]]
+//
+// The results are `wikilink` nodes with the `target`, the optional `anchor` and the `label`
+// (the link as written without a label) as attributes and the label as their text.
+// `resolve_wikilinks` marks each link with `exists` using a lookup of the known pages,
+// links to a section of the same page always exist.
+// ------------------------
+
+use crate::base::*;
+use crate::markdown::{MarkdownDetector, node, class_attributes};
+use crate::markdown::line::trim;
+use crate::types::{Queue, Value};
+
+fn split_once(text: &[char], separator: char) -> (Queue, Option<Queue>) {
+    match text.iter().position(|token| *token == separator) {
+        Some(index) => (text[..index].to_vec(), Some(text[index + 1..].to_vec())),
+        None => (text.to_vec(), None)
+    }
+}
+
+pub fn detect_wikilink(queue: &mut Queue) -> Option<Result> {
+    if !queue.starts_with(&['[', '[']) {
+        return None;
+    }
+
+    let end = queue.iter().skip(2).position(|token| ['[', ']', '\n'].contains(token))? + 2;
+
+    if queue.get(end + 1) != Some(&']') || queue[end] != ']' {
+        return None;
+    }
+
+    let (link, label) = split_once(&queue[2..end], '|');
+    let (target, anchor) = split_once(&link, '#');
+
+    let target = trim(&target);
+    let anchor = anchor.map(|anchor| trim(&anchor)).filter(|anchor| !anchor.is_empty());
+
+    if target.is_empty() && anchor.is_none() {
+        return None;
+    }
+
+    let label = match label.map(|label| trim(&label)) {
+        Some(label) if !label.is_empty() => label,
+        _ => trim(&link)
+    };
+
+    queue.drain(0..end + 2);
+
+    let mut attributes = class_attributes();
+
+    attributes.set("target", Value::String(target.iter().collect()));
+
+    if let Some(anchor) = anchor {
+        attributes.set("anchor", Value::String(anchor.iter().collect()));
+    }
+
+    attributes.set("label", Value::String(label.iter().collect()));
+
+    Some(
+        node(
+            MarkdownDetector::WikilinkDetector,
+            "wikilink",
+            attributes,
+            None,
+            Some(
+                vec![
+                    Result::new(Detector::RawDetector, Some(label), None, None)
+                ]
+            )
+        )
+    )
+}
+
+/// Sets the `exists` attribute of all wikilinks in the result tree, `exists` is asked for each target page
+pub fn resolve_wikilinks<E>(
+    results: Vec<Result>,
+    exists: &mut dyn FnMut(&str) -> std::result::Result<bool, E>
+) -> std::result::Result<Vec<Result>, E> {
+    results.into_iter().map(|mut result| {
+        if result.detector == Detector::MarkdownDetector(MarkdownDetector::WikilinkDetector) {
+            if let Value::Dict(mut attributes) = result.get_property("attributes") {
+                let target = attributes.get("target").to_str().unwrap_or_default();
+
+                let found = target.is_empty() || exists(&target)?;
+
+                attributes.set("exists", Value::Boolean(found));
+
+                result.set_property("attributes", Value::Dict(attributes));
+            }
+        }
+
+        if let Some(children) = result.children {
+            result.children = Some(resolve_wikilinks(children, exists)?);
+        }
+
+        Ok(result)
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::{Queue, Value};
+
+    use super::*;
+
+    fn attribute(result: &Result, key: &str) -> Value {
+        match result.get_property("attributes") {
+            Value::Dict(attributes) => attributes.get(key),
+            _ => Value::NoneValue
+        }
+    }
+
+    fn wikilink(input: &str) -> Option<(Value, Value, Value, String)> {
+        let mut queue = Queue::from_string(input.to_string());
+
+        let result = detect_wikilink(&mut queue)?;
+
+        Some((attribute(&result, "target"), attribute(&result, "anchor"), attribute(&result, "label"), queue.to_string()))
+    }
+
+    fn string(text: &str) -> Value {
+        Value::String(text.to_string())
+    }
+
+    #[test]
+    fn test_wikilink() {
+        assert_eq!(wikilink("[[Page Name]] rest"), Some((string("Page Name"), Value::NoneValue, string("Page Name"), " rest".to_string())));
+        assert_eq!(wikilink("[[Page#Section | the label]]"), Some((string("Page"), string("Section"), string("the label"), "".to_string())));
+        assert_eq!(wikilink("[[#Section]]"), Some((string(""), string("Section"), string("#Section"), "".to_string())));
+
+        assert_eq!(wikilink("[[]]"), None);
+        assert_eq!(wikilink("[[a [b] c]]"), None);
+        assert_eq!(wikilink("[[a\nb]]"), None);
+        assert_eq!(wikilink("[[a]"), None);
+    }
+
+    #[test]
+    fn test_resolve_wikilinks() {
+        let mut queue = Queue::from_string("[[Home]] [[Missing]] [[#Top]]".to_string());
+
+        let results = queue.consume_any(&[Detector::MarkdownDetector(MarkdownDetector::WikilinkDetector)]).unwrap();
+
+        let results = resolve_wikilinks::<()>(results, &mut |target| Ok(target == "Home")).unwrap();
+
+        let exists = results.iter()
+            .filter(|result| result.detector != Detector::RawDetector)
+            .map(|result| attribute(result, "exists"))
+            .collect::<Vec<Value>>();
+
+        assert_eq!(exists, vec![Value::Boolean(true), Value::Boolean(false), Value::Boolean(true)]);
+
+        assert_eq!(resolve_wikilinks(results, &mut |_| Err("error")), Err("error"));
+    }
+}
@@ -16,6 +16,7 @@ pub type Queue = Vec<char>;
 #[derive(Debug, Clone)]
 pub enum Value {
     NoneValue,
+    Null, // An explicit `None`, kept in dicts unlike `NoneValue`
     String(String),
     Boolean(bool),
     Float(f64),
@@ -27,7 +28,7 @@ pub enum Value {
 }
 
 impl Value {
-    pub fn new(ref value: &(dyn Any)) -> Self {
+    pub fn new(value: &dyn Any) -> Self {
         if let Some(string) = value.downcast_ref::<String>() {
             Self::String(string.clone())
         } else if let Some(boolean) = value.downcast_ref::<bool>() {
@@ -62,12 +63,12 @@ impl Value {
             Self::Queue(_) => Some(String::from("Queue")),
             Self::Dict(_) => Some(String::from("Properties")),
             Self::Array(_) => Some(String::from("Array")),
-            Self::NoneValue => None
+            Self::NoneValue | Self::Null => None
         }
     }
 
-    pub fn from(ref value: &(dyn Any)) -> Self {
-        Self::new(*value)
+    pub fn from(value: &dyn Any) -> Self {
+        Self::new(value)
     }
 
     pub fn value(&self) -> Option<&dyn Any> {
@@ -80,7 +81,7 @@ impl Value {
             Self::Queue(queue) => Some(queue),
             Self::Dict(properties) => Some(properties),
             Self::Array(array) => Some(array),
-            Self::NoneValue => None
+            Self::NoneValue | Self::Null => None
         }
     }
 } 
@@ -95,7 +96,9 @@ impl PartialEq for Value {
             (Self::Result(result_1), Self::Result(result_2)) => result_1 == result_2,
             (Self::Queue(queue_1), Self::Queue(queue_2)) => queue_1 == queue_2,
             (Self::Dict(properties_1), Self::Dict(properties_2)) => properties_1 == properties_2,
+            (Self::Array(array_1), Self::Array(array_2)) => array_1 == array_2,
             (Self::NoneValue, Self::NoneValue) => true,
+            (Self::Null, Self::Null) => true,
             _ => false
         }
     }
@@ -151,7 +154,7 @@ impl IntoPy<PyObject> for Value {
 
                 substrings.into_py(py)
             },
-            Self::NoneValue => py.None()
+            Self::NoneValue | Self::Null => py.None()
         }
     }
 }
@@ -162,6 +165,12 @@ pub struct Dict {
     pub properties: HashMap<String, Value>
 }
 
+impl Default for Dict {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Dict {
     pub fn new() -> Self {
         Self {
@@ -179,7 +188,7 @@ impl Dict {
         properties
     }
 
-    pub fn from(pairs: Vec<(String, &(dyn Any))>) -> Self {
+    pub fn from(pairs: Vec<(String, &dyn Any)>) -> Self {
         let mut properties = Self::new();
 
         for (key, value) in pairs {
@@ -216,7 +225,11 @@ impl Dict {
     }
 
     pub fn empty(&self) -> bool {
-        self.len() == 0
+        self.properties.is_empty()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.empty()
     }
 }
 